@@ -0,0 +1,354 @@
+import io
+import re
+import sys
+
+from PIL import Image
+
+import pytesseract
+
+from capture.screenshot import CaptureError, normalize_image, to_luma_image
+
+
+class OcrError(CaptureError):
+    pass
+
+
+# Tuned tesseract settings for text the default page model mishandles.
+# "code" turns off the dictionary (which "corrects" identifiers and hex
+# strings into dictionary words), restricts the character set to what
+# source code and logs contain, and assumes a uniform block of text.
+OCR_PROFILES = {
+    "code": (
+        "--psm 6 -c load_system_dawg=0 -c load_freq_dawg=0"
+        " -c tessedit_char_whitelist="
+        "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789"
+        "_-+*/=<>()[]{}.,:;#&|!?@$%^~'\"\\"
+    ),
+}
+
+
+def extract_text(source, lang="eng", profile=None):
+    """Run OCR and return the extracted text.
+
+    Accepts a CaptureData, a PIL image, or a filesystem path. Works entirely
+    in memory for the first two, so captures never have to touch disk just to
+    be read. An OCR_PROFILES name can be given for content like code where
+    the default settings do more harm than good.
+    """
+    image = getattr(source, "image", source)
+    if isinstance(image, str):
+        image = Image.open(image)
+    image = to_luma_image(image)  # tesseract works on luma anyway; convert once
+    tess_config = ""
+    if profile is not None:
+        try:
+            tess_config = OCR_PROFILES[profile]
+        except KeyError:
+            raise OcrError(
+                "unknown OCR profile %r (choose from %s)"
+                % (profile, ", ".join(sorted(OCR_PROFILES)))
+            )
+    try:
+        if "+" in lang:
+            return _extract_best(image, lang.split("+"))
+        return pytesseract.image_to_string(
+            image, lang=lang, config=tess_config
+        ).strip()
+    except pytesseract.TesseractNotFoundError:
+        raise OcrError("tesseract is not installed")
+
+
+def _block_words(image, lang):
+    """Per-block word lists with confidences for one language pass."""
+    data = pytesseract.image_to_data(
+        image, lang=lang, output_type=pytesseract.Output.DICT
+    )
+    blocks = {}
+    for word, conf, block in zip(data["text"], data["conf"], data["block_num"]):
+        word = word.strip()
+        try:
+            conf = float(conf)
+        except (TypeError, ValueError):
+            continue
+        if not word or conf < 0:  # -1 marks layout rows, not words
+            continue
+        words, confs = blocks.setdefault(block, ([], []))
+        words.append(word)
+        confs.append(conf)
+    return blocks
+
+
+def _extract_best(image, langs):
+    """Recognize with each language and keep the best result per block.
+
+    Mixed-language screenshots (an English UI quoting Japanese text, say)
+    come out wrong whichever single order `eng+jpn` is given, because
+    tesseract biases the whole page toward the first script. Running the
+    languages separately and comparing mean word confidence per layout
+    block lets each paragraph win with the script it is actually in.
+    Block numbering comes from layout analysis, which is script-agnostic
+    enough that the ids line up across passes.
+    """
+    per_lang = [_block_words(image, lang) for lang in langs]
+    block_ids = sorted(set().union(*per_lang)) if per_lang else []
+    lines = []
+    for block in block_ids:
+        best_words, best_conf = None, -1.0
+        for blocks in per_lang:
+            if block not in blocks:
+                continue
+            words, confs = blocks[block]
+            mean = sum(confs) / len(confs)
+            if mean > best_conf:
+                best_words, best_conf = words, mean
+        if best_words:
+            lines.append(" ".join(best_words))
+    return "\n".join(lines)
+
+
+def extract_table(source, lang="eng", fmt="csv"):
+    """Reconstruct a table from word boxes and emit CSV or Markdown.
+
+    Plain OCR flattens screenshotted tables (from PDFs, dashboards, ...)
+    into word soup. Instead, cluster word boxes into rows by vertical
+    overlap and infer column boundaries from the gaps in the horizontal
+    projection of all boxes, then read each cell out of the grid.
+    """
+    image = getattr(source, "image", source)
+    if isinstance(image, str):
+        image = Image.open(image)
+    image = to_luma_image(image)
+    try:
+        data = pytesseract.image_to_data(
+            image, lang=lang, output_type=pytesseract.Output.DICT
+        )
+    except pytesseract.TesseractNotFoundError:
+        raise OcrError("tesseract is not installed")
+
+    boxes = []
+    for word, left, top, width, height in zip(
+        data["text"], data["left"], data["top"], data["width"], data["height"]
+    ):
+        word = word.strip()
+        if word:
+            boxes.append((int(left), int(top), int(width), int(height), word))
+    if not boxes:
+        raise OcrError("no text found to tabulate")
+
+    # Rows: group boxes whose vertical centres sit within half a typical
+    # line height of each other.
+    heights = sorted(box[3] for box in boxes)
+    line_height = heights[len(heights) // 2]
+    rows = []
+    for box in sorted(boxes, key=lambda b: b[1] + b[3] / 2):
+        centre = box[1] + box[3] / 2
+        if rows and abs(centre - rows[-1][0]) <= line_height / 2:
+            rows[-1][1].append(box)
+        else:
+            rows.append((centre, [box]))
+
+    # Columns: gaps in the x-projection of every box that are wider than a
+    # character or two are column separators.
+    right = max(box[0] + box[2] for box in boxes)
+    covered = bytearray(right + 1)
+    for left, _top, width, _height, _word in boxes:
+        for x in range(left, left + width + 1):
+            covered[x] = 1
+    gap_threshold = max(2 * line_height // 3, 4)
+    separators = []
+    gap_start = None
+    for x in range(right + 1):
+        if not covered[x]:
+            gap_start = x if gap_start is None else gap_start
+        elif gap_start is not None:
+            if x - gap_start >= gap_threshold and gap_start > 0:
+                separators.append((gap_start + x) // 2)
+            gap_start = None
+
+    def column_of(box):
+        centre = box[0] + box[2] / 2
+        for index, separator in enumerate(separators):
+            if centre < separator:
+                return index
+        return len(separators)
+
+    table = []
+    for _centre, members in rows:
+        cells = [""] * (len(separators) + 1)
+        for box in sorted(members, key=lambda b: b[0]):
+            index = column_of(box)
+            cells[index] = (cells[index] + " " + box[4]).strip()
+        table.append(cells)
+
+    if fmt == "markdown":
+        lines = ["| " + " | ".join(table[0]) + " |"]
+        lines.append("|" + "|".join(" --- " for _ in table[0]) + "|")
+        for cells in table[1:]:
+            lines.append("| " + " | ".join(cells) + " |")
+        return "\n".join(lines)
+    import csv
+
+    buf = io.StringIO()
+    csv.writer(buf).writerows(table)
+    return buf.getvalue().rstrip("\n")
+
+
+def translate_text(text, spec, config):
+    """Translate OCR output according to a 'source:target' language spec.
+
+    Uses the external command from [translate] command in the config when
+    present (invoked with the source and target languages as arguments and
+    the text on stdin); otherwise posts to a LibreTranslate-compatible
+    endpoint from [translate] url.
+    """
+    import json
+    import subprocess
+    import urllib.request
+
+    source, _, target = spec.partition(":")
+    if not source or not target:
+        raise OcrError("invalid --translate spec %r, expected source:target" % spec)
+
+    command = config.get("translate", "command")
+    if command:
+        try:
+            result = subprocess.run(
+                command.split() + [source, target],
+                input=text.encode(),
+                capture_output=True,
+                check=True,
+            )
+        except (OSError, subprocess.CalledProcessError) as exc:
+            raise OcrError("translate command failed: %s" % exc)
+        return result.stdout.decode().strip()
+
+    url = config.get("translate", "url")
+    if not url:
+        raise OcrError("no [translate] command or url configured")
+    request = urllib.request.Request(
+        url.rstrip("/") + "/translate",
+        data=json.dumps({"q": text, "source": source, "target": target}).encode(),
+        headers={"Content-Type": "application/json"},
+    )
+    try:
+        with urllib.request.urlopen(request, timeout=10) as response:
+            return json.load(response)["translatedText"]
+    except (OSError, KeyError, ValueError) as exc:
+        raise OcrError("translation request failed: %s" % exc)
+
+
+def locate_text(source, needle, lang="eng"):
+    """Find a phrase on screen and return its bounding box (x, y, w, h).
+
+    Matches a run of consecutive OCR words case-insensitively, so scripts
+    can target "the dialog containing X" without knowing its geometry.
+    Coordinates are relative to the source image.
+    """
+    image = getattr(source, "image", source)
+    if isinstance(image, str):
+        image = Image.open(image)
+    image = to_luma_image(image)
+    try:
+        data = pytesseract.image_to_data(
+            image, lang=lang, output_type=pytesseract.Output.DICT
+        )
+    except pytesseract.TesseractNotFoundError:
+        raise OcrError("tesseract is not installed")
+    words = []
+    for word, left, top, width, height in zip(
+        data["text"], data["left"], data["top"], data["width"], data["height"]
+    ):
+        if word.strip():
+            words.append((word.strip(), int(left), int(top), int(width), int(height)))
+    wanted = needle.lower().split()
+    if not wanted:
+        raise OcrError("empty search string")
+    for start in range(len(words) - len(wanted) + 1):
+        run = words[start:start + len(wanted)]
+        if all(w.lower().strip(".,:;!?") == n for (w, *_), n in zip(run, wanted)):
+            left = min(w[1] for w in run)
+            top = min(w[2] for w in run)
+            right = max(w[1] + w[3] for w in run)
+            bottom = max(w[2] + w[4] for w in run)
+            return (left, top, right - left, bottom - top)
+    raise OcrError("text %r not found on screen" % needle)
+
+
+# Good enough for OCR output; trailing sentence punctuation is stripped so
+# "see https://example.com." doesn't produce a dead link.
+_URL_RE = re.compile(r"https?://[^\s<>\"'\)\]]+")
+
+
+def find_urls(text):
+    """URLs detected in OCR output, in reading order."""
+    return [url.rstrip(".,;") for url in _URL_RE.findall(text)]
+
+
+def open_link(url):
+    """Open a URL in the default browser, detached from this process."""
+    import subprocess
+
+    try:
+        subprocess.Popen(
+            ["xdg-open", url],
+            stdout=subprocess.DEVNULL,
+            stderr=subprocess.DEVNULL,
+        )
+    except OSError:
+        raise OcrError("xdg-open is not installed")
+
+
+def speak_text(text):
+    """Read text aloud through speech-dispatcher as an accessibility aid."""
+    import subprocess
+
+    try:
+        subprocess.run(["spd-say", "--wait", "--", text], check=True)
+    except OSError:
+        raise OcrError("spd-say is not installed (install speech-dispatcher)")
+    except subprocess.CalledProcessError:
+        raise OcrError("spd-say failed")
+
+
+# Keep only the most recent extractions; this is a recovery aid, not an archive.
+HISTORY_LIMIT = 20
+
+
+def record_history(text):
+    """Append an OCR result to the history log (most recent first)."""
+    from utils import state
+
+    if not text:
+        return
+    history = load_history()
+    if history and history[0] == text:
+        return  # re-running OCR on the same capture shouldn't duplicate
+    state.set("ocr_history", [text] + history[: HISTORY_LIMIT - 1])
+
+
+def load_history():
+    """Recent OCR results, most recent first."""
+    from utils import state
+
+    history = state.get("ocr_history")
+    return history if isinstance(history, list) else []
+
+
+def load_image(path):
+    """Load an image for OCR from a path, or from stdin when path is '-'.
+
+    Reading stdin lets the command compose with other grabbers, e.g.
+    `grim - | openshotx ocr -`.
+    """
+    if path == "-":
+        data = sys.stdin.buffer.read()
+        if not data:
+            raise OcrError("no image data on stdin")
+        try:
+            return normalize_image(Image.open(io.BytesIO(data)))
+        except OSError:
+            raise OcrError("stdin did not contain a recognizable image")
+    try:
+        return normalize_image(Image.open(path))
+    except OSError:
+        raise OcrError("could not open image %r" % path)
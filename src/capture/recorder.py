@@ -0,0 +1,368 @@
+import os
+import shutil
+import signal
+import subprocess
+import tempfile
+
+from capture.screenshot import CaptureError, is_wayland
+from utils import state
+
+
+class RecordingError(CaptureError):
+    pass
+
+
+def _take_idle_inhibit():
+    """Best-effort org.freedesktop.ScreenSaver inhibit for a recording.
+
+    systemd-inhibit --what=idle only blocks logind's IdleAction; GNOME and
+    KDE blank and lock the screen through the ScreenSaver interface, so the
+    inhibit has to be taken there. Returns an (interface, cookie) handle for
+    _release_idle_inhibit, or None when the bus or service isn't available.
+    The cookie is tied to our D-Bus connection, so the desktop drops it on
+    its own if the recording process dies without releasing it.
+    """
+    try:
+        from PyQt5.QtDBus import QDBusConnection, QDBusInterface, QDBusMessage
+    except ImportError:
+        return None
+    bus = QDBusConnection.sessionBus()
+    if not bus.isConnected():
+        return None
+    interface = QDBusInterface(
+        "org.freedesktop.ScreenSaver",
+        "/org/freedesktop/ScreenSaver",
+        "org.freedesktop.ScreenSaver",
+        bus,
+    )
+    reply = interface.call("Inhibit", "OpenShotX", "Screen recording in progress")
+    if reply.type() == QDBusMessage.ErrorMessage or not reply.arguments():
+        return None
+    return interface, reply.arguments()[0]
+
+
+def _release_idle_inhibit(handle):
+    if handle is None:
+        return
+    interface, cookie = handle
+    interface.call("UnInhibit", cookie)
+
+
+class Recorder:
+    """Screen recording via wf-recorder (Wayland) or ffmpeg x11grab (X11).
+
+    Recordings are written as one segment per pause/resume cycle and spliced
+    together on finalize, so pausing mid-demo produces a single seamless file.
+    """
+
+    def __init__(self, region=None, output=None, vfr=True):
+        self.region = region  # (x, y, w, h) or None for the whole screen
+        self.output = output
+        self.vfr = vfr  # keep compositor frame timing instead of forcing CFR
+        self.process = None
+        self.segments = []
+        self.paused = False
+        self._inhibit = None
+
+    def _segment_path(self):
+        suffix = os.path.splitext(self.output)[1] or ".mp4"
+        fd, path = tempfile.mkstemp(prefix="openshotx-seg-", suffix=suffix)
+        os.close(fd)
+        return path
+
+    def _command(self, segment):
+        if is_wayland():
+            # wf-recorder already encodes with the compositor's screencopy
+            # presentation timestamps, so irregular frame delivery is handled.
+            cmd = ["wf-recorder", "-f", segment]
+            if self.region:
+                x, y, w, h = self.region
+                cmd += ["-g", "%d,%d %dx%d" % (x, y, w, h)]
+            return cmd
+        display = os.environ.get("DISPLAY", ":0")
+        cmd = ["ffmpeg", "-hide_banner", "-loglevel", "error", "-y", "-f", "x11grab"]
+        if self.vfr:
+            # Stamp frames with wallclock PTS and pass them through unchanged,
+            # so recordings neither drift nor stutter when frames arrive
+            # irregularly, instead of resampling to a nominal constant rate.
+            cmd += ["-use_wallclock_as_timestamps", "1"]
+        if self.region:
+            x, y, w, h = self.region
+            cmd += ["-video_size", "%dx%d" % (w, h), "-i", "%s+%d,%d" % (display, x, y)]
+        else:
+            cmd += ["-i", display]
+        if self.vfr:
+            cmd += ["-vsync", "passthrough"]
+        if os.environ.get("OPENSHOTX_GPU"):
+            # Opt-in VAAPI pipeline: scaling, format conversion, and encode
+            # all happen on the GPU, which is what makes 4K recording viable
+            # on laptops whose CPUs can't keep up with software x264.
+            from utils.gpu import RENDER_NODE
+
+            cmd += [
+                "-vaapi_device", RENDER_NODE,
+                "-vf", "format=nv12,hwupload",
+                "-c:v", "h264_vaapi",
+            ]
+        return cmd + [segment]
+
+    def _start_segment(self):
+        segment = self._segment_path()
+        cmd = self._command(segment)
+        try:
+            self.process = subprocess.Popen(cmd)
+        except OSError as exc:
+            raise RecordingError("could not start recorder: %s" % exc)
+        self.segments.append(segment)
+
+    def _end_segment(self):
+        if self.process is None:
+            return
+        self.process.send_signal(signal.SIGINT)  # let the encoder finalize
+        self.process.wait()
+        self.process = None
+
+    def start(self):
+        if self.process is not None:
+            raise RecordingError("recording already started")
+        # Hold an idle inhibit for the lifetime of the recording so the
+        # screensaver or lock screen can't ruin a long capture.
+        self._inhibit = _take_idle_inhibit()
+        self._start_segment()
+        state.set(
+            "recording", {"controller": os.getpid(), "output": self.output}
+        )
+
+    def pause(self):
+        if self.paused or self.process is None:
+            return
+        self._end_segment()
+        self.paused = True
+
+    def resume(self):
+        if not self.paused:
+            return
+        self._start_segment()
+        self.paused = False
+
+    def toggle_pause(self):
+        if self.paused:
+            self.resume()
+        else:
+            self.pause()
+
+    def retarget(self, region):
+        """Move the recording to a new region by starting a fresh segment.
+
+        Used by window-following: the segments are spliced on finalize, so the
+        output stays locked to the window as it moves around. Only the origin
+        follows — the frame size stays locked to what the recording started
+        with (cropping or over-grabbing if the window is resized), because
+        finalize splices with a stream copy and concatenating segments of
+        different dimensions produces a corrupt file.
+        """
+        if self.paused:
+            return
+        if self.region is not None:
+            x, y = region[0], region[1]
+            region = (x, y, self.region[2], self.region[3])
+        if region == self.region:
+            return
+        self._end_segment()
+        self.region = region
+        self._start_segment()
+
+    def wait(self):
+        if self.process is not None:
+            self.process.wait()
+
+    def stop(self, progress=None):
+        self._end_segment()
+        _release_idle_inhibit(self._inhibit)
+        self._inhibit = None
+        state.delete("recording")
+        self._finalize(progress=progress)
+
+    def _finalize(self, progress=None):
+        segments = [seg for seg in self.segments if os.path.exists(seg)]
+        self.segments = []
+        if not segments:
+            return
+        if len(segments) == 1:
+            shutil.move(segments[0], self.output)
+            return
+        if progress:
+            progress(0.0, "splicing %d segments" % len(segments))
+        # Splice the pause/resume segments with the concat demuxer.
+        list_path = segments[0] + ".list"
+        with open(list_path, "w") as handle:
+            for seg in segments:
+                handle.write("file '%s'\n" % seg)
+        try:
+            subprocess.run(
+                [
+                    "ffmpeg", "-hide_banner", "-loglevel", "error", "-y",
+                    "-f", "concat", "-safe", "0", "-i", list_path,
+                    "-c", "copy", self.output,
+                ],
+                check=True,
+            )
+        except (OSError, subprocess.CalledProcessError) as exc:
+            raise RecordingError("could not splice recording segments: %s" % exc)
+        finally:
+            os.remove(list_path)
+            for seg in segments:
+                try:
+                    os.remove(seg)
+                except OSError:
+                    pass
+        if progress:
+            progress(1.0, "finalized " + os.path.basename(self.output))
+
+
+class WindowFollower:
+    """Background poller that keeps a recording locked onto a moving window."""
+
+    def __init__(self, recorder, window_id, interval=1.0):
+        import threading
+
+        self.recorder = recorder
+        self.window_id = window_id
+        self.interval = interval
+        self._stop = threading.Event()
+        self._thread = threading.Thread(target=self._run, daemon=True)
+
+    def start(self):
+        self._thread.start()
+
+    def stop(self):
+        self._stop.set()
+        self._thread.join()
+
+    def _run(self):
+        from capture import windows
+
+        while not self._stop.wait(self.interval):
+            try:
+                info = windows.get_geometry(self.window_id)
+            except windows.WindowError:
+                return  # window closed; let the recording run out where it was
+            self.recorder.retarget(info.region)
+
+
+def _parse_timespec(spec):
+    """Accept '90', '2s', or '1:30' and return seconds for ffmpeg."""
+    spec = spec.strip().rstrip("s")
+    if ":" in spec:
+        minutes, _, seconds = spec.rpartition(":")
+        return str(int(minutes) * 60 + float(seconds))
+    return spec
+
+
+def _run_ffmpeg(args):
+    try:
+        subprocess.run(
+            ["ffmpeg", "-hide_banner", "-loglevel", "error", "-y"] + args, check=True
+        )
+    except OSError:
+        raise RecordingError("ffmpeg is not installed")
+    except subprocess.CalledProcessError as exc:
+        raise RecordingError("ffmpeg failed: %s" % exc)
+
+
+def trim(source, output, start=None, end=None):
+    """Cut a recording down to [start, end] without re-encoding."""
+    args = []
+    if start:
+        args += ["-ss", _parse_timespec(start)]
+    if end:
+        args += ["-to", _parse_timespec(end)]
+    _run_ffmpeg(args + ["-i", source, "-c", "copy", output])
+    return output
+
+
+def convert(source, output):
+    """Convert a recording to another container/format by extension.
+
+    GIF output goes through a two-pass palette so it doesn't come out dithered
+    to mush, which is the usual ffmpeg incantation nobody remembers.
+    """
+    if output.lower().endswith(".gif"):
+        _run_ffmpeg(
+            [
+                "-i", source,
+                "-filter_complex",
+                "[0:v]fps=12,split[a][b];[a]palettegen[p];[b][p]paletteuse",
+                output,
+            ]
+        )
+    else:
+        _run_ffmpeg(["-i", source, output])
+    return output
+
+
+class WebcamBubble:
+    """Picture-in-picture webcam feed shown on top of the screen being recorded.
+
+    Rendered as a borderless always-on-top ffplay window, so it is captured as
+    part of the recording like everything else on screen.
+    """
+
+    CORNERS = ("top-left", "top-right", "bottom-left", "bottom-right")
+
+    def __init__(self, device="/dev/video0", corner="bottom-right", size=240):
+        self.device = device
+        self.corner = corner
+        self.size = size
+        self.process = None
+
+    def _position(self, screen_width, screen_height):
+        margin = 24
+        x = margin if "left" in self.corner else screen_width - self.size - margin
+        y = margin if "top" in self.corner else screen_height - self.size - margin
+        return x, y
+
+    def start(self, screen_width, screen_height):
+        x, y = self._position(screen_width, screen_height)
+        try:
+            self.process = subprocess.Popen(
+                [
+                    "ffplay", "-hide_banner", "-loglevel", "error",
+                    "-f", "v4l2", "-i", self.device,
+                    "-noborder", "-alwaysontop",
+                    "-x", str(self.size), "-y", str(self.size),
+                    "-left", str(x), "-top", str(y),
+                ],
+                stdout=subprocess.DEVNULL,
+                stderr=subprocess.DEVNULL,
+            )
+        except OSError as exc:
+            raise RecordingError("could not start webcam overlay: %s" % exc)
+
+    def stop(self):
+        if self.process is not None:
+            self.process.terminate()
+            self.process.wait()
+            self.process = None
+
+
+def _signal_controller(signum, missing_message):
+    active = state.get("recording")
+    if active is None:
+        raise RecordingError("no recording in progress")
+    try:
+        os.kill(active["controller"], signum)
+    except (OSError, KeyError):
+        state.delete("recording")
+        raise RecordingError(missing_message)
+    return active.get("output")
+
+
+def stop_active():
+    """Stop a recording started by another invocation, via the state file."""
+    return _signal_controller(signal.SIGINT, "recording process is gone; state cleared")
+
+
+def toggle_pause_active():
+    """Pause or resume the active recording from another invocation."""
+    return _signal_controller(signal.SIGUSR1, "recording process is gone; state cleared")
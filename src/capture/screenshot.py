@@ -0,0 +1,685 @@
+import io
+import os
+import re
+import subprocess
+from dataclasses import dataclass, field
+
+from PIL import Image
+
+
+@dataclass
+class Monitor:
+    name: str
+    x: int
+    y: int
+    width: int
+    height: int
+    primary: bool = False
+    rotation: str = "normal"  # RandR transform: normal, left, right, inverted
+
+
+@dataclass
+class CaptureData:
+    """An in-memory capture plus everything we know about where it came from."""
+
+    image: Image.Image
+    region: tuple = None  # (x, y, w, h) in screen coordinates, if applicable
+    metadata: dict = field(default_factory=dict)
+    # Effective HiDPI scale of the session the pixels came from; 2.0 means
+    # the image is physical pixels at twice the logical size.
+    scale: float = 1.0
+    # Bytes per row of the raw reply this capture came from, when it came
+    # from a raw buffer; None for captures decoded from PNG. X servers pad
+    # scanlines to their unit, so this can exceed width * bytes_per_pixel.
+    stride: int = None
+
+    @property
+    def width(self):
+        return self.image.width
+
+    @property
+    def height(self):
+        return self.image.height
+
+    def to_png_bytes(self):
+        buf = io.BytesIO()
+        self.image.save(buf, format="PNG")
+        return buf.getvalue()
+
+    def validate(self):
+        """Sanity-check the capture, returning a list of issue strings.
+
+        Lets callers feeding untrusted data get structured problems instead
+        of exceptions from deep inside the save/OCR pipeline. An empty list
+        means the capture is usable.
+        """
+        issues = []
+        if self.image is None:
+            return ["capture has no image"]
+        if self.image.width <= 0 or self.image.height <= 0:
+            issues.append(
+                "zero-sized image (%dx%d)" % (self.image.width, self.image.height)
+            )
+        if self.image.mode not in ("RGBA", "RGB", "L"):
+            issues.append("unexpected pixel mode %r" % self.image.mode)
+        if self.region is not None:
+            if len(self.region) != 4:
+                issues.append("region must be (x, y, w, h), got %r" % (self.region,))
+            else:
+                _x, _y, w, h = self.region
+                if w <= 0 or h <= 0:
+                    issues.append("region has non-positive size %dx%d" % (w, h))
+        if not isinstance(self.metadata, dict):
+            issues.append("metadata must be a dict, got %s" % type(self.metadata).__name__)
+        if self.stride is not None and self.image is not None:
+            bytes_per_pixel = len(self.image.getbands())
+            if self.stride < self.image.width * bytes_per_pixel:
+                issues.append(
+                    "stride %d is smaller than a row of pixels (%d)"
+                    % (self.stride, self.image.width * bytes_per_pixel)
+                )
+        return issues
+
+
+class CaptureError(Exception):
+    pass
+
+
+class SelectionCancelled(Exception):
+    """The user backed out of a selection; an outcome, not a failure.
+
+    Deliberately not a CaptureError so it can't be swallowed by generic
+    error handling: the CLI maps it to exit code 2 and keybinding scripts
+    can tell cancel apart from breakage.
+    """
+
+
+# Pixel formats callers may request at capture time; values are PIL modes.
+# Converting once in the backend beats every consumer handling a zoo of
+# formats downstream.
+PIXEL_FORMATS = {
+    "RGBA32": "RGBA",
+    "RGB24": "RGB",
+    "GRAY8": "L",
+}
+
+
+def is_wayland():
+    return bool(os.environ.get("WAYLAND_DISPLAY"))
+
+
+_XRANDR_MONITOR_RE = re.compile(
+    r"^\s*\d+:\s+(\+?\*?)(\S+)\s+(\d+)/\d+x(\d+)/\d+\+(\d+)\+(\d+)"
+)
+
+
+def _display_env(display):
+    """Environment for child grabbers, with DISPLAY overridden when requested.
+
+    Lets captures target nested Xephyr/Xvfb sessions or an ssh-forwarded
+    display without affecting the rest of the process.
+    """
+    if display is None:
+        return None
+    env = dict(os.environ)
+    env["DISPLAY"] = display
+    return env
+
+
+_XRANDR_OUTPUT_RE = re.compile(
+    r"^(\S+) connected (?:primary )?\d+x\d+\+\d+\+\d+ (left|right|inverted)"
+)
+
+
+def _query_rotations(display=None):
+    """Map of output name -> RandR rotation for transformed outputs."""
+    try:
+        out = subprocess.run(
+            ["xrandr", "--query"],
+            capture_output=True,
+            text=True,
+            check=True,
+            env=_display_env(display),
+        ).stdout
+    except (OSError, subprocess.CalledProcessError):
+        return {}
+    rotations = {}
+    for line in out.splitlines():
+        match = _XRANDR_OUTPUT_RE.match(line)
+        if match:
+            rotations[match.group(1)] = match.group(2)
+    return rotations
+
+
+def upright(image, rotation):
+    """Undo an output transform so rotated monitors are captured upright."""
+    transpose = {
+        "left": Image.ROTATE_270,
+        "right": Image.ROTATE_90,
+        "inverted": Image.ROTATE_180,
+    }.get(rotation)
+    if transpose is None:
+        return image
+    return image.transpose(transpose)
+
+
+def list_monitors(display=None):
+    """Enumerate connected monitors with their layout positions."""
+    try:
+        out = subprocess.run(
+            ["xrandr", "--listmonitors"],
+            capture_output=True,
+            text=True,
+            check=True,
+            env=_display_env(display),
+        ).stdout
+    except (OSError, subprocess.CalledProcessError):
+        raise CaptureError("could not enumerate monitors (is xrandr available?)")
+    rotations = _query_rotations(display)
+    monitors = []
+    for line in out.splitlines():
+        match = _XRANDR_MONITOR_RE.match(line)
+        if match:
+            flags, name, w, h, x, y = match.groups()
+            monitors.append(
+                Monitor(
+                    name=name,
+                    x=int(x),
+                    y=int(y),
+                    width=int(w),
+                    height=int(h),
+                    primary="*" in flags,
+                    rotation=rotations.get(name, "normal"),
+                )
+            )
+    if not monitors:
+        raise CaptureError("no monitors found")
+    return monitors
+
+
+def primary_monitor(display=None):
+    monitors = list_monitors(display)
+    for monitor in monitors:
+        if monitor.primary:
+            return monitor
+    return monitors[0]
+
+
+def _swap_pixel_bytes(buffer):
+    """Reverse the byte order within each 32-bit pixel word.
+
+    MSB_FIRST servers (remote/legacy big-endian X) deliver A,R,G,B per
+    word where LSB servers deliver B,G,R,A; interpreting one as the other
+    produces wildly wrong colors, not subtly wrong ones.
+    """
+    out = bytearray(len(buffer))
+    out[0::4] = buffer[3::4]
+    out[1::4] = buffer[2::4]
+    out[2::4] = buffer[1::4]
+    out[3::4] = buffer[0::4]
+    return bytes(out)
+
+
+def raw_to_capture(buffer, width, height, stride=None, byte_order="LSB"):
+    """Wrap a raw 32-bit ARGB reply in a CaptureData, honouring padding.
+
+    X ZPixmap replies and QImage scanlines both pack each pixel into one
+    ARGB word (bytes B,G,R,A in LSB layout) and pad each row to the
+    server's scanline unit, so a capture of an unusual width shears
+    diagonally if the stride is assumed to be width * 4. PIL skips the
+    padding when given the real stride. Pass byte_order="MSB" for
+    big-endian hosts; the pixel words are swapped into LSB layout before
+    decoding (stride is always word-aligned for 32-bit visuals, so the
+    padding swaps harmlessly with them).
+    """
+    stride = stride if stride is not None else width * 4
+    if byte_order == "MSB":
+        buffer = _swap_pixel_bytes(bytes(buffer))
+    image = Image.frombuffer("RGBA", (width, height), buffer, "raw", "BGRA", stride, 1)
+    return CaptureData(image=image, stride=stride)
+
+
+def raw30_to_capture(buffer, width, height, stride=None, byte_order="LSB"):
+    """Decode x2r10g10b10 words from a depth-30 (10bpc) TrueColor visual.
+
+    Depth-30 displays pack each pixel into one 32-bit word with ten bits
+    per channel; feeding that to the 8-bit RGBA decoder produces garbage.
+    PIL has no 10-bit RGB mode, so the channels are reduced to their top
+    eight bits, which is lossless for everything a screenshot is used for.
+    """
+    import struct
+
+    stride = stride if stride is not None else width * 4
+    fmt = (">" if byte_order == "MSB" else "<") + "%dI" % width
+    out = bytearray(width * height * 4)
+    offset = 0
+    for y in range(height):
+        row = struct.unpack_from(fmt, buffer, y * stride)
+        for word in row:
+            out[offset] = (word >> 22) & 0xFF
+            out[offset + 1] = (word >> 12) & 0xFF
+            out[offset + 2] = (word >> 2) & 0xFF
+            out[offset + 3] = 255
+            offset += 4
+    image = Image.frombuffer("RGBA", (width, height), bytes(out), "raw", "RGBA", 0, 1)
+    return CaptureData(image=image, stride=stride)
+
+
+def to_luma_image(source):
+    """Grayscale (luma) view of a capture, as a public API.
+
+    Accepts a CaptureData or a PIL image. OCR, QR detection, diffing, and
+    thresholding all want the same conversion, so it lives here instead of
+    being re-implemented per consumer.
+    """
+    image = getattr(source, "image", source)
+    return image.convert("L")
+
+
+def query_gamma(display=None):
+    """Current per-channel gamma of the primary output, from xrandr --verbose.
+
+    Night-light tools (redshift/gammastep) work by skewing these, which is
+    what makes evening screenshots come out orange.
+    """
+    try:
+        out = subprocess.run(
+            ["xrandr", "--verbose"],
+            capture_output=True,
+            text=True,
+            check=True,
+            env=_display_env(display),
+        ).stdout
+    except (OSError, subprocess.CalledProcessError):
+        return (1.0, 1.0, 1.0)
+    for line in out.splitlines():
+        line = line.strip()
+        if line.startswith("Gamma:"):
+            try:
+                r, g, b = line.split(":", 1)[1].strip().split(":")
+                return (float(r), float(g), float(b))
+            except ValueError:
+                break
+    return (1.0, 1.0, 1.0)
+
+
+def undo_gamma(image, gamma):
+    """Invert per-channel gamma so night-light tinted captures look neutral."""
+    r, g, b = gamma
+    if (r, g, b) == (1.0, 1.0, 1.0):
+        return image
+    lut = []
+    for channel_gamma in (r, g, b):
+        lut += [
+            min(255, int((value / 255.0) ** channel_gamma * 255 + 0.5))
+            for value in range(256)
+        ]
+    lut += list(range(256))  # leave alpha untouched
+    return image.convert("RGBA").point(lut)
+
+
+def tone_map(image):
+    """Bring a >8-bit-per-channel frame down to displayable 8-bit.
+
+    Compositors with HDR/10-bit buffers hand us 16-bit PNG data; a plain
+    convert() truncates to the low byte and produces washed-out or clipped
+    results, so rescale against the actual peak value instead.
+    """
+    if image.mode not in ("I", "I;16", "I;16B"):
+        return image
+    peak = max(image.getextrema()[1], 1)
+    return image.point(lambda value: value * 255 // peak).convert("L")
+
+
+# When MemAvailable drops below this, raw capture pixels are spooled to a
+# memory-mapped temp file instead of living on the heap; tune with
+# OPENSHOTX_SPOOL_THRESHOLD_MB (0 disables spooling).
+SPOOL_THRESHOLD_MB = int(os.environ.get("OPENSHOTX_SPOOL_THRESHOLD_MB", "512"))
+
+
+def _available_memory_mb():
+    try:
+        with open("/proc/meminfo") as handle:
+            for line in handle:
+                if line.startswith("MemAvailable:"):
+                    return int(line.split()[1]) // 1024
+    except (OSError, ValueError, IndexError):
+        pass
+    return None
+
+
+def spool_image(image):
+    """Re-back a large capture with a memory-mapped temp file when RAM is low.
+
+    A stitched 3×4K RGBA capture is ~100MB of pixels; on a small machine
+    holding that plus the encoder's working set on the heap can OOM. The
+    mapped file is unlinked immediately, so the kernel pages pixels in and
+    out as conversions stream over them and cleans up when the image dies.
+    """
+    if SPOOL_THRESHOLD_MB <= 0:
+        return image
+    available = _available_memory_mb()
+    if available is None or available > SPOOL_THRESHOLD_MB:
+        return image
+    import mmap
+    import tempfile
+
+    raw = image.tobytes()
+    if len(raw) < 1 << 20:  # not worth a file for small captures
+        return image
+    with tempfile.TemporaryFile(prefix="openshotx-spool-") as spool:
+        spool.write(raw)
+        spool.flush()
+        mapped = mmap.mmap(spool.fileno(), len(raw))
+    del raw
+    return Image.frombuffer(image.mode, image.size, mapped, "raw", image.mode, 0, 1)
+
+
+# How long to wait on the grabber before assuming the portal backend is
+# wedged; overridable with OPENSHOTX_CAPTURE_TIMEOUT (seconds).
+GRAB_TIMEOUT = float(os.environ.get("OPENSHOTX_CAPTURE_TIMEOUT", "30"))
+
+
+def _grab_png(args, display=None, pixel_format="RGBA32"):
+    """Run an external grabber that writes PNG to stdout and wrap the result."""
+    try:
+        mode = PIXEL_FORMATS[pixel_format]
+    except KeyError:
+        raise CaptureError(
+            "unknown pixel format %r (choose from %s)"
+            % (pixel_format, ", ".join(sorted(PIXEL_FORMATS)))
+        )
+    try:
+        result = subprocess.run(
+            args,
+            capture_output=True,
+            check=True,
+            env=_display_env(display),
+            timeout=GRAB_TIMEOUT,
+        )
+    except OSError as exc:
+        raise CaptureError("%s is not installed" % args[0]) from exc
+    except subprocess.TimeoutExpired as exc:
+        raise CaptureError(
+            "%s timed out after %.0fs; the screenshot portal may be wedged "
+            "(try restarting xdg-desktop-portal)" % (args[0], GRAB_TIMEOUT)
+        ) from exc
+    except subprocess.CalledProcessError as exc:
+        raise CaptureError(
+            "%s failed: %s" % (args[0], exc.stderr.decode(errors="replace").strip())
+        ) from exc
+    image = Image.open(io.BytesIO(result.stdout))
+    return spool_image(normalize_image(image).convert(mode))
+
+
+def normalize_image(image):
+    """Bring any loaded image to a safe 8-bit mode before conversion.
+
+    Paletted (P/PA) images must be expanded through their palette and 16-bit
+    grayscale tone-mapped; a bare convert() would truncate or drop the
+    palette and silently corrupt the pixels.
+    """
+    if image.mode in ("P", "PA"):
+        return image.convert("RGBA")
+    if image.mode in ("I", "I;16", "I;16B"):
+        return tone_map(image)
+    return image
+
+
+# The Qt X connection is opened once and reused for every grab in the
+# process; watch/interval/daemon modes would otherwise reconnect per frame.
+# A dead connection (server restart, session handoff) can't be reopened
+# in-process, so after this many consecutive failures the fast path is
+# retired and every later grab goes through the external grabbers, which
+# connect fresh on each run.
+_SHM_MAX_FAILURES = 3
+_shm_failures = 0
+
+
+def _grab_shm(region=None, display=None, pixel_format="RGBA32"):
+    """In-process X11 grab through Qt's XCB image path, or None.
+
+    Qt fetches pixels over MIT-SHM when the server supports it, so nothing
+    streams through the X socket and no grabber process is forked — a large
+    win on 4K and multi-monitor setups. Returns None whenever the path
+    can't be used (Wayland, foreign display, no Qt, OPENSHOTX_NO_SHM set,
+    or a connection that has started failing) so callers fall back to the
+    external grabbers.
+    """
+    global _shm_failures
+    if is_wayland() or display is not None or os.environ.get("OPENSHOTX_NO_SHM"):
+        return None
+    if _shm_failures >= _SHM_MAX_FAILURES:
+        return None
+    try:
+        from PyQt5.QtWidgets import QApplication
+    except ImportError:
+        return None
+    try:
+        app = QApplication.instance() or QApplication([])
+        screen = app.primaryScreen()
+        if screen is None:
+            return None
+        if region is None:
+            # the root window spans the whole virtual desktop, not just the
+            # primary output, so grab its full extent explicitly
+            virtual = screen.virtualGeometry()
+            region = (virtual.x(), virtual.y(), virtual.width(), virtual.height())
+        x, y, w, h = region
+        pixmap = screen.grabWindow(0, x, y, w, h)
+        if pixmap.isNull():
+            _shm_failures += 1
+            return None
+        data = _decode_qimage(pixmap.toImage())
+    except Exception:
+        _shm_failures += 1
+        return None  # any Qt hiccup just means "use the slow path"
+    _shm_failures = 0
+    mode = PIXEL_FORMATS.get(pixel_format)
+    if mode is None:
+        return None
+    data.image = spool_image(normalize_image(data.image).convert(mode))
+    return data
+
+
+def _decode_qimage(qimage):
+    """CaptureData from a QImage's raw scanlines, without a PNG round-trip.
+
+    Qt hands the server's ZPixmap back nearly as-is: scanlines padded to
+    the server's unit (hence the stride), pixel words in host byte order,
+    and on deep-color setups 30-bit x2r10g10b10 packing — exactly the
+    cases raw_to_capture and raw30_to_capture exist for. Skipping the PNG
+    encode/decode also saves a full frame compression per grab.
+    """
+    import sys
+
+    from PyQt5.QtGui import QImage
+
+    byte_order = "MSB" if sys.byteorder == "big" else "LSB"
+    fmt = qimage.format()
+    deep = (
+        QImage.Format_RGB30,
+        QImage.Format_A2RGB30_Premultiplied,
+        QImage.Format_BGR30,
+        QImage.Format_A2BGR30_Premultiplied,
+    )
+    if fmt in deep:
+        if fmt != QImage.Format_RGB30:
+            qimage = qimage.convertToFormat(QImage.Format_RGB30)
+        decoder = raw30_to_capture
+    else:
+        if fmt not in (QImage.Format_RGB32, QImage.Format_ARGB32):
+            qimage = qimage.convertToFormat(QImage.Format_ARGB32)
+        decoder = raw_to_capture
+    bits = qimage.bits()
+    bits.setsize(qimage.byteCount())
+    return decoder(
+        bytes(bits),
+        qimage.width(),
+        qimage.height(),
+        stride=qimage.bytesPerLine(),
+        byte_order=byte_order,
+    )
+
+
+def capture_region(region, display=None, pixel_format="RGBA32"):
+    """Capture a rectangular screen region and return CaptureData."""
+    x, y, w, h = region.as_tuple() if hasattr(region, "as_tuple") else region
+    if is_wayland() and display is None:
+        image = _grab_png(
+            ["grim", "-g", "%d,%d %dx%d" % (x, y, w, h), "-"],
+            pixel_format=pixel_format,
+        )
+    else:
+        data = _grab_shm((x, y, w, h), display, pixel_format)
+        if data is not None:
+            data.region = (x, y, w, h)
+            return data
+        image = _grab_png(
+            ["maim", "-g", "%dx%d+%d+%d" % (w, h, x, y), "--format", "png", "/dev/stdout"],
+            display=display,
+            pixel_format=pixel_format,
+        )
+    return CaptureData(image=image, region=(x, y, w, h))
+
+
+def capture_monitors(monitors, display=None):
+    """Capture and stitch a subset of monitors into one image.
+
+    Used by --only/--exclude so a TV or portrait output can be left out of the
+    combined "screen" capture without giving up multi-monitor stitching.
+    """
+    from utils.editor import composite_layout
+
+    crops = []
+    for monitor in monitors:
+        region = (monitor.x, monitor.y, monitor.width, monitor.height)
+        image = capture_region(region, display=display).image
+        if monitor.rotation != "normal" and image.size == (monitor.height, monitor.width):
+            # Transformed output delivered sideways pixel data; stand it up.
+            image = upright(image, monitor.rotation)
+        if image.size != (monitor.width, monitor.height):
+            # HiDPI output delivering physical pixels: normalize to the
+            # monitor's logical size so mixed 1x/2x setups stitch without
+            # mismatched sizes or gaps.
+            image = image.resize((monitor.width, monitor.height), Image.LANCZOS)
+        crops.append((image, region))
+    return CaptureData(image=composite_layout(crops))
+
+
+def detect_scale(display=None):
+    """Effective HiDPI scale factor of the session (1.0 = no scaling).
+
+    X11 advertises it as Xft.dpi in the resource database (96 dpi per 1x).
+    Wayland compositors apply per-output scale before grim ever sees the
+    pixels, so Qt's devicePixelRatio is asked instead when available.
+    """
+    if not is_wayland() or display is not None:
+        try:
+            out = subprocess.run(
+                ["xrdb", "-query"],
+                capture_output=True,
+                text=True,
+                check=True,
+                env=_display_env(display),
+            ).stdout
+            for line in out.splitlines():
+                if line.startswith("Xft.dpi:"):
+                    return round(float(line.split(":", 1)[1]) / 96.0, 2)
+        except (OSError, subprocess.CalledProcessError, ValueError):
+            pass
+        return 1.0
+    try:
+        from PyQt5.QtWidgets import QApplication
+
+        app = QApplication.instance() or QApplication([])
+        screen = app.primaryScreen()
+        if screen is not None:
+            return float(screen.devicePixelRatio())
+    except Exception:
+        pass
+    return 1.0
+
+
+def to_logical(data):
+    """Downscale a physical-pixel capture to its logical size.
+
+    On a 2x display a "800x600" grab is really 1600x1200 physical pixels;
+    docs and chat destinations usually want the logical size so the image
+    doesn't render double-sized next to the text.
+    """
+    if data.scale and data.scale > 1:
+        data.image = data.image.resize(
+            (round(data.width / data.scale), round(data.height / data.scale)),
+            Image.LANCZOS,
+        )
+        data.scale = 1.0
+    return data
+
+
+def mask_dead_zones(data, display=None, mode="black"):
+    """Clean up the uncovered parts of a mismatched multi-monitor layout.
+
+    When the X virtual screen spans monitors of different sizes, the
+    rectangles no output covers still exist in the root window, and the
+    server hands back whatever stale bytes happen to live there — old
+    frames, garbage, occasionally other users' leftovers. This paints those
+    dead rectangles with a solid color (any PIL color spec), or with
+    mode="crop" cuts the capture down to the bounding box of real outputs.
+    """
+    if mode in ("off", "no"):
+        return data
+    try:
+        monitors = list_monitors(display)
+    except CaptureError:
+        return data
+    image = data.image
+    virtual_w = max(m.x + m.width for m in monitors)
+    virtual_h = max(m.y + m.height for m in monitors)
+    # HiDPI roots deliver physical pixels; map logical layout coordinates
+    # onto whatever resolution the grab actually came back at.
+    scale_x = image.width / virtual_w
+    scale_y = image.height / virtual_h
+
+    def scaled(monitor):
+        return (
+            round(monitor.x * scale_x),
+            round(monitor.y * scale_y),
+            round((monitor.x + monitor.width) * scale_x),
+            round((monitor.y + monitor.height) * scale_y),
+        )
+
+    if mode == "crop":
+        left = min(scaled(m)[0] for m in monitors)
+        top = min(scaled(m)[1] for m in monitors)
+        data.image = image.crop(
+            (left, top, max(scaled(m)[2] for m in monitors), max(scaled(m)[3] for m in monitors))
+        )
+        return data
+    from PIL import ImageDraw
+
+    mask = Image.new("L", image.size, 255)
+    draw = ImageDraw.Draw(mask)
+    for monitor in monitors:
+        x0, y0, x1, y1 = scaled(monitor)
+        draw.rectangle((x0, y0, x1 - 1, y1 - 1), fill=0)
+    if mask.getextrema() == (0, 0):
+        return data  # outputs tile the whole root; nothing dead to paint
+    image.paste(mode, (0, 0), mask)
+    return data
+
+
+def capture_fullscreen(display=None, pixel_format="RGBA32"):
+    """Capture the entire desktop across all monitors."""
+    if is_wayland() and display is None:
+        image = _grab_png(["grim", "-"], pixel_format=pixel_format)
+    else:
+        data = _grab_shm(None, display, pixel_format)
+        if data is not None:
+            return data
+        image = _grab_png(
+            ["maim", "--format", "png", "/dev/stdout"],
+            display=display,
+            pixel_format=pixel_format,
+        )
+    return CaptureData(image=image)
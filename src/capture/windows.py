@@ -0,0 +1,301 @@
+import subprocess
+from dataclasses import dataclass
+
+from capture.screenshot import CaptureError, SelectionCancelled
+
+
+class WindowError(CaptureError):
+    pass
+
+
+@dataclass
+class WindowInfo:
+    window_id: str
+    desktop: int
+    x: int
+    y: int
+    width: int
+    height: int
+    wm_class: str
+    title: str
+    pid: int = 0
+
+    @property
+    def region(self):
+        return (self.x, self.y, self.width, self.height)
+
+    def metadata(self):
+        """Window identity for CaptureData.metadata, so filename templates,
+        history, and notifications can say what was captured."""
+        return {
+            "window_title": self.title,
+            "window_class": self.wm_class,
+            "window_pid": self.pid,
+        }
+
+
+def list_windows():
+    """Enumerate managed windows with geometry via wmctrl.
+
+    wmctrl reads the window manager's EWMH client list; when it isn't
+    installed, fall back to walking _NET_CLIENT_LIST ourselves through
+    xprop/xdotool so `capture window` still works.
+    """
+    try:
+        out = subprocess.run(
+            ["wmctrl", "-lxGp"], capture_output=True, text=True, check=True
+        ).stdout
+    except (OSError, subprocess.CalledProcessError):
+        return _list_windows_ewmh()
+    windows = []
+    for line in out.splitlines():
+        parts = line.split(None, 9)
+        if len(parts) < 10:
+            continue
+        window_id, desktop, pid, x, y, w, h, wm_class, _host, title = parts
+        windows.append(
+            WindowInfo(
+                window_id=window_id,
+                desktop=int(desktop),
+                x=int(x),
+                y=int(y),
+                width=int(w),
+                height=int(h),
+                wm_class=wm_class,
+                title=title,
+                pid=int(pid),
+            )
+        )
+    return windows
+
+
+def capture_window_pixels(window_id, display=None):
+    """Grab a window's own drawable instead of cropping the root image.
+
+    Under a compositing window manager the drawable holds the window's true
+    contents, so the capture comes out clean even when the window is
+    partially covered by others. Returns PNG bytes, or None when the path
+    isn't available (no ImageMagick, no compositor) so the caller falls
+    back to the root-crop capture.
+    """
+    from capture.screenshot import _display_env
+
+    try:
+        result = subprocess.run(
+            ["import", "-window", str(window_id), "png:-"],
+            capture_output=True,
+            check=True,
+            timeout=10,
+            env=_display_env(display),
+        )
+    except (OSError, subprocess.SubprocessError):
+        return None
+    return result.stdout or None
+
+
+def _list_windows_ewmh():
+    """Walk the root window's _NET_CLIENT_LIST without wmctrl."""
+    try:
+        out = subprocess.run(
+            ["xprop", "-root", "_NET_CLIENT_LIST"],
+            capture_output=True,
+            text=True,
+            check=True,
+        ).stdout
+    except (OSError, subprocess.CalledProcessError):
+        raise WindowError("could not list windows (install wmctrl or xprop)")
+    _, _, ids = out.partition("#")
+    windows = []
+    for window_id in ids.replace(",", " ").split():
+        if not window_id.startswith("0x"):
+            continue
+        try:
+            info = get_geometry(window_id)
+        except WindowError:
+            continue  # window went away between the list and the query
+        for attr, command in (
+            ("title", "getwindowname"),
+            ("pid", "getwindowpid"),
+            ("desktop", "get_desktop_for_window"),
+        ):
+            try:
+                value = subprocess.run(
+                    ["xdotool", command, window_id],
+                    capture_output=True,
+                    text=True,
+                    check=True,
+                ).stdout.strip()
+                setattr(info, attr, value if attr == "title" else int(value))
+            except (OSError, subprocess.CalledProcessError, ValueError):
+                pass
+        windows.append(info)
+    if not windows:
+        raise WindowError("no windows found via _NET_CLIENT_LIST")
+    return windows
+
+
+def find_window(title=None, wm_class=None, desktop=None):
+    """First managed window whose title/class matches the given patterns.
+
+    Patterns are case-insensitive regular expressions searched as
+    substrings, so `--name Firefox` and `--name 'Mozilla.*'` both work.
+    desktop, when given, restricts the search to that virtual desktop
+    (windows report theirs through _NET_WM_DESKTOP).
+    """
+    import re
+
+    try:
+        title_re = re.compile(title, re.IGNORECASE) if title else None
+        class_re = re.compile(wm_class, re.IGNORECASE) if wm_class else None
+    except re.error as exc:
+        raise WindowError("invalid window pattern: %s" % exc)
+    for window in list_windows():
+        if desktop is not None and window.desktop != desktop:
+            continue
+        if title_re is not None and not title_re.search(window.title):
+            continue
+        if class_re is not None and not class_re.search(window.wm_class):
+            continue
+        return window
+    raise WindowError(
+        "no window matching %s"
+        % " and ".join(
+            "%s %r" % (label, pattern)
+            for label, pattern in (
+                ("title", title),
+                ("class", wm_class),
+                ("desktop", desktop),
+            )
+            if pattern is not None
+        )
+    )
+
+
+def find_window_by_pid(pid):
+    """The biggest top-level window owned by a process.
+
+    The window manager reports ownership through _NET_WM_PID (surfaced by
+    wmctrl's pid column). A process can own several windows — splash
+    screens, tool palettes — so the largest one is taken as the window the
+    caller means.
+    """
+    candidates = [window for window in list_windows() if window.pid == pid]
+    if not candidates:
+        raise WindowError("no window owned by pid %d" % pid)
+    return max(candidates, key=lambda w: w.width * w.height)
+
+
+def window_containing(x, y):
+    """The smallest managed window whose frame contains the given point.
+
+    Smallest wins because a dialog sits inside its parent's rectangle and
+    stacking order isn't available from wmctrl.
+    """
+    best = None
+    for window in list_windows():
+        if window.x <= x < window.x + window.width and window.y <= y < window.y + window.height:
+            if best is None or window.width * window.height < best.width * best.height:
+                best = window
+    if best is None:
+        raise WindowError("no window at %d,%d" % (x, y))
+    return best
+
+
+def current_desktop():
+    """Index of the active virtual desktop, via wmctrl."""
+    try:
+        out = subprocess.run(
+            ["wmctrl", "-d"], capture_output=True, text=True, check=True
+        ).stdout
+    except (OSError, subprocess.CalledProcessError):
+        raise WindowError("could not list desktops (is wmctrl installed?)")
+    for line in out.splitlines():
+        parts = line.split()
+        if len(parts) >= 2 and parts[1] == "*":
+            return int(parts[0])
+    raise WindowError("no active desktop found")
+
+
+def switch_desktop(index):
+    """Switch to a virtual desktop by index."""
+    try:
+        subprocess.run(["wmctrl", "-s", str(index)], check=True)
+    except (OSError, subprocess.CalledProcessError):
+        raise WindowError("could not switch to desktop %s" % index)
+
+
+def active_window():
+    """The currently focused window, via xdotool."""
+    try:
+        window_id = subprocess.run(
+            ["xdotool", "getactivewindow"], capture_output=True, text=True, check=True
+        ).stdout.strip()
+    except (OSError, subprocess.CalledProcessError):
+        raise WindowError("could not find the active window (is xdotool installed?)")
+    return _fill_identity(get_geometry(window_id))
+
+
+def _fill_identity(info):
+    """Best-effort title/pid lookup via xdotool; geometry is what matters."""
+    for attr, command in (("title", "getwindowname"), ("pid", "getwindowpid")):
+        try:
+            value = subprocess.run(
+                ["xdotool", command, info.window_id],
+                capture_output=True,
+                text=True,
+                check=True,
+            ).stdout.strip()
+            setattr(info, attr, int(value) if attr == "pid" else value)
+        except (OSError, subprocess.CalledProcessError, ValueError):
+            pass
+    return info
+
+
+def select_window_by_click():
+    """Grab the pointer and return the window the user clicks.
+
+    xdotool resolves the click to the top-level client window, so clicking
+    anywhere inside an application picks the whole window rather than some
+    inner widget.
+    """
+    try:
+        window_id = subprocess.run(
+            ["xdotool", "selectwindow"], capture_output=True, text=True, check=True
+        ).stdout.strip()
+    except OSError:
+        raise WindowError("could not start the click selector (is xdotool installed?)")
+    except subprocess.CalledProcessError:
+        raise SelectionCancelled("window selection cancelled")
+    if not window_id:
+        raise SelectionCancelled("window selection cancelled")
+    return _fill_identity(get_geometry(window_id))
+
+
+def get_geometry(window_id):
+    """Fresh geometry for a window id, as a WindowInfo."""
+    try:
+        out = subprocess.run(
+            ["xdotool", "getwindowgeometry", "--shell", str(window_id)],
+            capture_output=True,
+            text=True,
+            check=True,
+        ).stdout
+    except (OSError, subprocess.CalledProcessError):
+        raise WindowError("window %s is gone" % window_id)
+    values = {}
+    for line in out.splitlines():
+        key, _, value = line.partition("=")
+        values[key.strip()] = value.strip()
+    try:
+        return WindowInfo(
+            window_id=str(window_id),
+            desktop=-1,
+            x=int(values["X"]),
+            y=int(values["Y"]),
+            width=int(values["WIDTH"]),
+            height=int(values["HEIGHT"]),
+            wm_class="",
+            title="",
+        )
+    except (KeyError, ValueError):
+        raise WindowError("could not parse geometry for window %s" % window_id)
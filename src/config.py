@@ -0,0 +1,122 @@
+import os
+import configparser
+
+CONFIG_DIR = os.path.join(
+    os.environ.get("XDG_CONFIG_HOME", os.path.expanduser("~/.config")), "openshotx"
+)
+CONFIG_PATH = os.path.join(CONFIG_DIR, "config.ini")
+
+
+class Config:
+    """Wrapper around the ini-style config file at ~/.config/openshotx/config.ini."""
+
+    def __init__(self, path=CONFIG_PATH):
+        self.path = path
+        self.parser = configparser.ConfigParser()
+        if os.path.exists(path):
+            self.parser.read(path)
+
+    def get(self, section, key, fallback=None):
+        return self.parser.get(section, key, fallback=fallback)
+
+    def presets(self):
+        """Named region presets from the [presets] section.
+
+        Each value is a geometry string (pixel or percentage based), e.g.
+
+            [presets]
+            left-half = 50%x100%+0+0
+            top-bar = 100%x48+0+0
+        """
+        if not self.parser.has_section("presets"):
+            return {}
+        return dict(self.parser.items("presets"))
+
+    def profile(self, name):
+        """Options bundled under a [profile.<name>] section, e.g.
+
+            [profile.blog]
+            format = webp
+            scale = 80
+
+        Raises KeyError for unknown profile names.
+        """
+        section = "profile." + name
+        if not self.parser.has_section(section):
+            raise KeyError(name)
+        return dict(self.parser.items(section))
+
+
+    def rules(self):
+        """Per-application capture rules from [rule.<match>] sections.
+
+        The section suffix is matched case-insensitively against the captured
+        window's class/app id, e.g.
+
+            [rule.keepassxc]
+            to = clipboard
+
+            [rule.mpv]
+            format = jpg
+        """
+        rules = []
+        for section in self.parser.sections():
+            if section.startswith("rule."):
+                rules.append((section[len("rule."):], dict(self.parser.items(section))))
+        return rules
+
+    def set_value(self, dotted_key, value):
+        """Set 'section.key' (e.g. save.subdirs, overlay.grid) and write the file."""
+        section, _, key = dotted_key.rpartition(".")
+        if not section or not key:
+            raise ValueError("expected section.key, got %r" % dotted_key)
+        if not self.parser.has_section(section):
+            self.parser.add_section(section)
+        self.parser.set(section, key, value)
+        os.makedirs(os.path.dirname(self.path), exist_ok=True)
+        with open(self.path, "w") as handle:
+            self.parser.write(handle)
+
+    def dump(self):
+        """The effective configuration as ini text."""
+        import io
+
+        buf = io.StringIO()
+        self.parser.write(buf)
+        return buf.getvalue().rstrip()
+
+    def validate(self):
+        """Sanity-check known sections, returning a list of problem strings."""
+        problems = []
+        known_sections = (
+            "presets",
+            "overlay",
+            "save",
+            "translate",
+            "record",
+            "notify",
+            "vault",
+            "email",
+            "print",
+        )
+        for section in self.parser.sections():
+            if section not in known_sections and not section.startswith(
+                ("profile.", "upload.", "rule.", "webhook.")
+            ):
+                problems.append("unknown section [%s]" % section)
+        grid = self.get("overlay", "grid")
+        if grid is not None and not grid.isdigit():
+            problems.append("[overlay] grid must be an integer, got %r" % grid)
+        from utils.geometry import parse_geometry, Region
+
+        reference = Region(0, 0, 1920, 1080)  # any monitor works for a parse check
+        for name, spec in self.presets().items():
+            try:
+                parse_geometry(spec, reference)
+            except ValueError:
+                problems.append("[presets] %s: invalid geometry %r" % (name, spec))
+        return problems
+
+
+def load_config():
+    return Config()
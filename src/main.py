@@ -1,17 +1,1484 @@
+import argparse
+import os
 import sys
-from PyQt5.QtWidgets import QApplication, QMainWindow
 
-class MainWindow(QMainWindow):
-    def __init__(self):
-        super().__init__()
-        self.setWindowTitle("OpenShot X - Screen Capture Tool")
-        self.setGeometry(100, 100, 600, 400)  # Set the dimensions of the window (x, y, width, height)
+from config import load_config
+from capture import screenshot
+from capture.screenshot import CaptureError, SelectionCancelled
+from utils import storage
+from utils.geometry import resolve_region
+
+
+def build_parser():
+    parser = argparse.ArgumentParser(prog="openshotx", description="Screen capture tool for Linux")
+    subparsers = parser.add_subparsers(dest="command")
+
+    capture = subparsers.add_parser("capture", help="take a screenshot")
+    capture.add_argument(
+        "target",
+        choices=["area", "screen", "window", "workspace", "monitor"],
+        help="what to capture",
+    )
+    capture.add_argument(
+        "selector",
+        nargs="?",
+        help="workspace index for `capture workspace`, or monitor index/name "
+        "for `capture monitor` (e.g. 1 or DP-1)",
+    )
+    capture.add_argument(
+        "--geometry",
+        help="region as WxH+X+Y (components may be percentages of the target "
+        "monitor, e.g. 50%%x50%%+25%%+25%%) or a preset name from [presets] in the config",
+    )
+    capture.add_argument("-o", "--output", help="output file path")
+    capture.add_argument(
+        "--json",
+        action="store_true",
+        help="emit a JSON result object (including cancelled: true on cancel)",
+    )
+    capture.add_argument(
+        "--undo-night-light",
+        action="store_true",
+        help="compensate redshift/gammastep color temperature in the capture",
+    )
+    capture.add_argument(
+        "--only",
+        metavar="NAMES",
+        help="comma-separated monitor names to include when capturing the screen",
+    )
+    capture.add_argument(
+        "--exclude",
+        metavar="NAMES",
+        help="comma-separated monitor names to leave out when capturing the screen",
+    )
+    capture.add_argument(
+        "--multi",
+        action="store_true",
+        help="with `capture window`, select several windows and composite them",
+    )
+    capture.add_argument(
+        "--background",
+        action="store_true",
+        help="return immediately and finish saving/uploading in the "
+        "background, notifying on completion (not compatible with --json)",
+    )
+    capture.add_argument(
+        "--every",
+        type=float,
+        metavar="SECONDS",
+        help="interval mode: keep capturing every N seconds until "
+        "interrupted (here --output names the frame directory, not a file)",
+    )
+    capture.add_argument(
+        "--count",
+        type=int,
+        help="with --every, stop after this many captures",
+    )
+    capture.add_argument(
+        "--freeze",
+        action="store_true",
+        help="with `capture area`, freeze the screen during selection so "
+        "animations don't change between selecting and capturing",
+    )
+    capture.add_argument(
+        "--watch",
+        action="store_true",
+        help="capture whenever the target changes instead of once "
+        "(screenshot-the-dashboard-when-it-updates automation)",
+    )
+    capture.add_argument(
+        "--debounce",
+        type=float,
+        default=2.0,
+        help="with --watch, minimum seconds between triggered captures",
+    )
+    capture.add_argument(
+        "--threshold",
+        type=float,
+        default=0.01,
+        help="with --watch, fraction of change that counts as an update",
+    )
+    capture.add_argument(
+        "--keep",
+        type=int,
+        metavar="M",
+        help="with --every, cycle through M ring-buffer filenames instead "
+        "of accumulating timestamped files (kiosk monitoring)",
+    )
+    capture.add_argument(
+        "--serve",
+        type=int,
+        metavar="PORT",
+        help="with --every, serve the latest frame over HTTP on localhost",
+    )
+    capture.add_argument(
+        "--snippet",
+        choices=["markdown", "org"],
+        help="print a ready-to-paste image link after delivery; paths under "
+        "[save] notes_dir in the config come out relative to it",
+    )
+    capture.add_argument(
+        "--workspace",
+        type=int,
+        metavar="N",
+        help="with `capture window`, look for the window on virtual desktop "
+        "N and grab its drawable without switching workspaces",
+    )
+    capture.add_argument(
+        "--logical",
+        action="store_true",
+        help="on HiDPI displays, downscale the capture to logical pixels "
+        "instead of keeping the physical-resolution (@2x) image",
+    )
+    capture.add_argument(
+        "--print",
+        action="store_true",
+        dest="print_copy",
+        help="also send the capture to the printer via lp, scaled to fit "
+        "the page ([print] in the config picks the queue and paper size)",
+    )
+    capture.add_argument(
+        "--keep-alpha",
+        action="store_true",
+        help="preserve per-pixel alpha from ARGB windows (transparent "
+        "terminals and the like) instead of the flattened on-screen pixels",
+    )
+    capture.add_argument(
+        "--click",
+        action="store_true",
+        help="with `capture window`, pick the window by clicking it instead "
+        "of using the thumbnail picker",
+    )
+    capture.add_argument(
+        "--pid",
+        type=int,
+        help="with `capture window`, pick the window owned by this process id",
+    )
+    capture.add_argument(
+        "--name",
+        help="with `capture window`, pick the window whose title matches "
+        "this pattern (case-insensitive regex) without prompting",
+    )
+    capture.add_argument(
+        "--class",
+        dest="wm_class",
+        help="with `capture window`, pick the window whose class matches "
+        "this pattern (case-insensitive regex) without prompting",
+    )
+    capture.add_argument(
+        "--containing",
+        metavar="TEXT",
+        help="with `capture window`, pick the window showing the given text "
+        "(found via OCR) instead of prompting",
+    )
+    capture.add_argument(
+        "--arrange",
+        choices=["layout", "row"],
+        default="layout",
+        help="composite multi-window captures with on-screen layout or in a row",
+    )
+    capture.add_argument(
+        "--display",
+        help="X display string to capture from (e.g. :1 for a nested/remote session)",
+    )
+    capture.add_argument(
+        "--to",
+        help="comma-separated output sinks: file, clipboard, upload:<service> "
+        "(default: file)",
+    )
+    capture.add_argument(
+        "--format",
+        choices=["png", "jpg", "webp", "pdf"],
+        help="output format; pdf wraps the capture in a one-page document "
+        "at its physical size",
+    )
+    capture.add_argument("--scale", type=int, help="scale the result to this percentage")
+    capture.add_argument(
+        "--quality", type=int, help="encoder quality for lossy formats (1-100)"
+    )
+    capture.add_argument(
+        "--delay", type=float, metavar="SEC", help="wait this many seconds before capturing"
+    )
+    capture.add_argument(
+        "--save-dialog",
+        action="store_true",
+        help="pick the destination with the native (portal-backed) save dialog",
+    )
+    capture.add_argument(
+        "--temp",
+        action="store_true",
+        help="save into the cache with automatic expiry instead of the Pictures folder",
+    )
+    capture.add_argument(
+        "--ocr-only",
+        action="store_true",
+        help="OCR the capture in memory and copy the text, writing no image file",
+    )
+    capture.add_argument(
+        "--translate",
+        metavar="SRC:DST",
+        help="translate OCR output between languages, e.g. de:en",
+    )
+    capture.add_argument(
+        "--speak", action="store_true", help="read OCR output aloud via speech-dispatcher"
+    )
+    capture.add_argument(
+        "--profile",
+        help="apply a named option bundle from a [profile.<name>] config section",
+    )
+
+    subparsers.add_parser("redo", help="repeat the previous capture with identical parameters")
+
+    ocr = subparsers.add_parser("ocr", help="extract text from an image")
+    ocr.add_argument(
+        "path",
+        help="image file, - to read image bytes from stdin, "
+        "or 'history' to list recent extractions, or 'live' for the "
+        "experimental hover-to-OCR overlay",
+    )
+    ocr.add_argument(
+        "rest",
+        nargs="*",
+        help="with 'history': `copy <n>` puts entry n back on the clipboard",
+    )
+    ocr.add_argument("--lang", default="eng", help="tesseract language code")
+    ocr.add_argument(
+        "--table",
+        nargs="?",
+        const="csv",
+        choices=["csv", "markdown"],
+        help="reconstruct a table from word positions instead of plain text",
+    )
+    ocr.add_argument(
+        "--translate",
+        metavar="SRC:DST",
+        help="translate OCR output between languages, e.g. de:en",
+    )
+    ocr.add_argument(
+        "--speak", action="store_true", help="read OCR output aloud via speech-dispatcher"
+    )
+    ocr.add_argument(
+        "--profile",
+        help="tuned recognition profile, e.g. 'code' for source/log screenshots",
+    )
+    ocr.add_argument(
+        "--open-links",
+        action="store_true",
+        help="open the first URL found in the text in the default browser",
+    )
+
+    remote = subparsers.add_parser(
+        "remote", help="run a capture on a remote machine over SSH"
+    )
+    remote.add_argument(
+        "--to", help="local delivery: file (default) or clipboard"
+    )
+    remote.add_argument("-o", "--output", help="local output file path")
+    remote.add_argument("host", help="SSH destination, e.g. admin@lab-03")
+    remote.add_argument(
+        "rest",
+        nargs=argparse.REMAINDER,
+        help="openshotx arguments to run remotely, e.g. capture screen",
+    )
+
+    serve = subparsers.add_parser(
+        "serve", help="serve requests for editor and plugin integrations"
+    )
+    serve.add_argument(
+        "--stdio",
+        action="store_true",
+        required=True,
+        help="speak newline-delimited JSON-RPC on stdin/stdout",
+    )
+
+    status = subparsers.add_parser("status", help="show capture counters and health")
+    status.add_argument(
+        "--serve",
+        type=int,
+        metavar="PORT",
+        help="serve /metrics (Prometheus) and /healthz on localhost",
+    )
+
+    history = subparsers.add_parser("history", help="review recent captures")
+    history.add_argument(
+        "action",
+        nargs="?",
+        choices=["list", "sheet"],
+        default="list",
+        help="list prints recent capture paths; sheet renders a contact "
+        "sheet of labelled thumbnails for weekly reviews",
+    )
+    history.add_argument(
+        "--since",
+        default="7d",
+        metavar="AGE",
+        help="how far back to look, e.g. 7d, 48h, 90m (default 7d)",
+    )
+    history.add_argument(
+        "--columns", type=int, default=4, help="thumbnails per contact-sheet row"
+    )
+    history.add_argument("-o", "--output", help="where to write the contact sheet")
+
+    state_cmd = subparsers.add_parser("state", help="manage runtime state")
+    state_cmd.add_argument("action", choices=["reset", "path"])
+
+    config_cmd = subparsers.add_parser("config", help="inspect and edit the configuration")
+    config_cmd.add_argument("action", choices=["show", "path", "set", "validate"])
+    config_cmd.add_argument("key", nargs="?", help="section.key for set")
+    config_cmd.add_argument("value", nargs="?", help="value for set")
+
+    subparsers.add_parser(
+        "portal-backend",
+        help="serve as the org.freedesktop.impl.portal.Screenshot provider",
+    )
+
+    monitors = subparsers.add_parser(
+        "monitors", help="list connected monitors with geometry and rotation"
+    )
+    monitors.add_argument("--display", help="X display to query, e.g. :1")
+
+    doctor = subparsers.add_parser("doctor", help="check dependencies and environment")
+    doctor.add_argument("--json", action="store_true", help="machine-readable report")
+
+    subparsers.add_parser("bench", help="benchmark capture, encode, and OCR performance")
+
+    record = subparsers.add_parser("record", help="record the screen")
+    record.add_argument(
+        "action",
+        nargs="?",
+        choices=["start", "window", "stop", "pause", "resume", "trim", "convert"],
+        default="start",
+        help="start records until Ctrl-C; window records the active window; "
+        "stop/pause/resume control it from elsewhere; trim/convert "
+        "post-process existing recordings",
+    )
+    record.add_argument("files", nargs="*", help="input (and output) files for trim/convert")
+    record.add_argument("--start", help="trim start, e.g. 2s or 1:30")
+    record.add_argument("--end", help="trim end, e.g. 30s or 2:00")
+    record.add_argument(
+        "--follow",
+        action="store_true",
+        help="with `record window`, keep tracking the window as it moves",
+    )
+    record.add_argument("--geometry", help="region to record as WxH+X+Y or a preset name")
+    record.add_argument("-o", "--output", help="output video path")
+    record.add_argument(
+        "--webcam",
+        action="store_true",
+        help="overlay the webcam as a picture-in-picture bubble",
+    )
+    record.add_argument(
+        "--webcam-corner",
+        choices=["top-left", "top-right", "bottom-left", "bottom-right"],
+        default="bottom-right",
+    )
+    record.add_argument(
+        "--show-input",
+        action="store_true",
+        help="overlay click ripples and pressed-key badges into the recording",
+    )
+    record.add_argument(
+        "--no-indicator",
+        action="store_true",
+        help="run headless without the floating stop/elapsed-time indicator",
+    )
+
+    return parser
+
+
+def apply_profile(args, config):
+    """Fill in unset capture options from the selected profile section."""
+    if not args.profile:
+        return
+    try:
+        profile = config.profile(args.profile)
+    except KeyError:
+        raise CaptureError("unknown profile %r" % args.profile)
+    for key in ("geometry", "output", "format", "scale", "quality", "to"):
+        if key in profile and getattr(args, key, None) is None:
+            value = profile[key]
+            setattr(args, key, int(value) if key in ("scale", "quality") else value)
+
+
+def apply_window_rules(args, config, metadata):
+    """Apply [rule.<match>] options for the app the capture came from.
+
+    Rules run after the pixels are grabbed (the class isn't known before),
+    so only delivery options are supported — geometry deliberately isn't.
+    """
+    window_class = str(metadata.get("window_class", "")).lower()
+    if not window_class:
+        return
+    for pattern, options in config.rules():
+        if pattern.lower() not in window_class:
+            continue
+        for key in ("output", "format", "scale", "quality", "to"):
+            if key in options and getattr(args, key, None) is None:
+                value = options[key]
+                setattr(args, key, int(value) if key in ("scale", "quality") else value)
+
+
+def _serve_latest_frame(port, latest):
+    """Tiny localhost HTTP endpoint returning the newest interval frame."""
+    from http.server import BaseHTTPRequestHandler, HTTPServer
+
+    class Handler(BaseHTTPRequestHandler):
+        def do_GET(self):
+            path = latest["path"]
+            if path is None or not os.path.exists(path):
+                self.send_error(503, "no frame captured yet")
+                return
+            with open(path, "rb") as handle:
+                body = handle.read()
+            self.send_response(200)
+            self.send_header("Content-Type", "image/" + (os.path.splitext(path)[1].lstrip(".") or "png"))
+            self.send_header("Content-Length", str(len(body)))
+            self.send_header("Cache-Control", "no-store")
+            self.end_headers()
+            self.wfile.write(body)
+
+        def log_message(self, *args):
+            pass  # status boards poll constantly; don't flood stderr
+
+    HTTPServer(("127.0.0.1", port), Handler).serve_forever()
+
+
+def cmd_interval_capture(args, config):
+    """Timed capture loop with encoding pushed to a background thread.
+
+    The grab keeps its cadence even when PNG encoding (or a slow Pictures
+    mount) takes longer than the interval: frames go onto a small queue and
+    a writer thread drains it, so only a sustained encode backlog ever
+    stalls the loop.
+    """
+    import queue
+    import threading
+    import time
+
+    region = None
+    if args.geometry:
+        monitor = screenshot.primary_monitor(display=args.display)
+        region = resolve_region(args.geometry, monitor, config.presets())
+    # Unlike everywhere else, --output names a directory here: the loop
+    # produces many frames, so a single file path makes no sense.
+    directory = args.output or storage.default_save_dir()
+    os.makedirs(directory, exist_ok=True)
+    frames = queue.Queue(maxsize=8)
+    latest = {"path": None}  # most recently *written* frame, for --serve
+
+    def writer():
+        while True:
+            item = frames.get()
+            if item is None:
+                return
+            data, path = item
+            try:
+                storage.save_capture(data, path=path, quality=args.quality)
+                latest["path"] = path
+            except Exception as exc:
+                # Swallow per-frame failures (ENOSPC, a vanished mount):
+                # a dead writer would leave the producer blocked forever
+                # on the bounded queue.
+                print("save failed: %s" % exc, file=sys.stderr)
+
+    if args.serve:
+        threading.Thread(
+            target=_serve_latest_frame, args=(args.serve, latest), daemon=True
+        ).start()
+
+    thread = threading.Thread(target=writer, daemon=True)
+    thread.start()
+    _graceful_termination()
+    taken = 0
+    next_tick = time.monotonic()
+    try:
+        while args.count is None or taken < args.count:
+            if region is not None:
+                data = screenshot.capture_region(region, display=args.display)
+            else:
+                data = screenshot.capture_fullscreen(display=args.display)
+            if args.keep:
+                # Ring buffer: a fixed set of names that the loop cycles
+                # through, so a kiosk never fills its disk.
+                name = "frame-%03d.%s" % (taken % args.keep, args.format or "png")
+            else:
+                name = storage.default_filename(args.format or "png")
+                if args.every < 1.0:
+                    # timestamps are second-resolution; keep fast frames distinct
+                    base, ext = os.path.splitext(name)
+                    name = "%s-%03d%s" % (base, taken, ext)
+            path = os.path.join(directory, name)
+            if not thread.is_alive():
+                raise CaptureError("frame writer died; stopping the capture loop")
+            frames.put((data, path))
+            if not args.json:
+                print(path)
+            taken += 1
+            next_tick += args.every
+            time.sleep(max(0.0, next_tick - time.monotonic()))
+    except KeyboardInterrupt:
+        pass  # Ctrl-C ends the session; flush what's queued
+    finally:
+        # Only hand the sentinel to a live writer; blocking on a full
+        # queue nobody is draining would hang shutdown.
+        while thread.is_alive():
+            try:
+                frames.put(None, timeout=1)
+                break
+            except queue.Full:
+                continue
+        thread.join()
+
+
+def cmd_watch_capture(args, config):
+    """Save a capture whenever the watched region changes.
+
+    This polls: a thumbnail-sized luma diff is sampled every half second.
+    That is a deliberate deviation from event-driven X Damage triggering,
+    which isn't reachable through the external-grabber architecture — it
+    means changes that appear and vanish within the sampling interval can
+    be missed, and the watch has a small constant cost even when nothing
+    changes. --debounce enforces a minimum gap between triggered captures
+    so an animating region doesn't produce one per frame; a change landing
+    inside that gap stays pending and is captured once the gap expires.
+    """
+    import time
+
+    from utils.testing import image_difference
+
+    region = None
+    if args.geometry:
+        monitor = screenshot.primary_monitor(display=args.display)
+        region = resolve_region(args.geometry, monitor, config.presets())
+
+    def sample():
+        if region is not None:
+            data = screenshot.capture_region(region, display=args.display)
+        else:
+            data = screenshot.capture_fullscreen(display=args.display)
+        thumb = screenshot.to_luma_image(data)
+        thumb.thumbnail((160, 160))
+        return data, thumb
+
+    _graceful_termination()
+    _, reference = sample()
+    last_saved = 0.0
+    taken = 0
+    try:
+        while args.count is None or taken < args.count:
+            time.sleep(0.5)
+            data, thumb = sample()
+            if image_difference(thumb, reference) <= args.threshold:
+                reference = thumb  # track slow drift below the threshold
+                continue
+            if time.monotonic() - last_saved < args.debounce:
+                # Keep the old reference: a change inside the debounce gap
+                # stays pending and triggers once the gap expires, rather
+                # than being compared away and lost.
+                continue
+            reference = thumb
+            path = storage.save_capture(
+                data,
+                args.output,
+                extension=args.format or "png",
+                quality=args.quality,
+            )
+            print(path)
+            last_saved = time.monotonic()
+            taken += 1
+    except KeyboardInterrupt:
+        pass
+
+
+def cmd_capture(args, config):
+    apply_profile(args, config)
+    if args.watch:
+        return cmd_watch_capture(args, config)
+    if args.every:
+        return cmd_interval_capture(args, config)
+    if not args.ocr_only:
+        storage.preflight_check(args.output)
+    if args.delay:
+        import time
+
+        time.sleep(args.delay)
+    if args.target == "screen":
+        if args.only or args.exclude:
+            monitors = screenshot.list_monitors(args.display)
+            if args.only:
+                wanted = {name.strip() for name in args.only.split(",")}
+                monitors = [m for m in monitors if m.name in wanted]
+            if args.exclude:
+                dropped = {name.strip() for name in args.exclude.split(",")}
+                monitors = [m for m in monitors if m.name not in dropped]
+            if not monitors:
+                raise CaptureError("no monitors left after --only/--exclude filtering")
+            data = screenshot.capture_monitors(monitors, display=args.display)
+        else:
+            data = screenshot.capture_fullscreen(display=args.display)
+            # Mixed-size layouts leave garbage in the uncovered parts of
+            # the virtual screen; [save] dead_zones picks a fill color,
+            # "crop", or "off".
+            data = screenshot.mask_dead_zones(
+                data,
+                display=args.display,
+                mode=config.get("save", "dead_zones", fallback="black"),
+            )
+    elif args.target == "workspace":
+        from capture import windows
+
+        if args.selector is None or not args.selector.isdigit():
+            raise CaptureError("capture workspace requires a workspace index")
+        # Switch over, grab, and hop back so documenting another workspace
+        # doesn't require doing the dance by hand.
+        previous = windows.current_desktop()
+        windows.switch_desktop(int(args.selector))
+        try:
+            import time
+
+            time.sleep(0.3)  # let the compositor finish the switch animation
+            data = screenshot.capture_fullscreen(display=args.display)
+        finally:
+            windows.switch_desktop(previous)
+    elif args.target == "monitor":
+        if args.selector is None:
+            raise CaptureError("capture monitor requires a monitor index or name")
+        monitors = screenshot.list_monitors(args.display)
+        if args.selector.isdigit():
+            index = int(args.selector)
+            if not 0 <= index < len(monitors):
+                raise CaptureError(
+                    "monitor index %d out of range (have %d)" % (index, len(monitors))
+                )
+            monitor = monitors[index]
+        else:
+            by_name = {m.name: m for m in monitors}
+            if args.selector not in by_name:
+                raise CaptureError(
+                    "no monitor named %r (have %s)"
+                    % (args.selector, ", ".join(sorted(by_name)))
+                )
+            monitor = by_name[args.selector]
+        data = screenshot.capture_monitors([monitor], display=args.display)
+    elif args.target == "window":
+        from capture import windows
+        from ui.widgets import pick_window
+
+        if args.pid:
+            chosen = windows.find_window_by_pid(args.pid)
+        elif args.name or args.wm_class or args.workspace is not None:
+            chosen = windows.find_window(
+                title=args.name, wm_class=args.wm_class, desktop=args.workspace
+            )
+        elif args.click:
+            chosen = windows.select_window_by_click()
+        elif args.containing:
+            frame = screenshot.capture_fullscreen(display=args.display)
+            from capture.ocr import locate_text
+
+            x, y, w, h = locate_text(frame, args.containing)
+            chosen = windows.window_containing(x + w // 2, y + h // 2)
+        else:
+            frame = screenshot.capture_fullscreen(display=args.display)
+            chosen = pick_window(windows.list_windows(), frame.image, multi=args.multi)
+        if chosen is None:
+            raise SelectionCancelled("window selection cancelled")
+        if isinstance(chosen, list):
+            from utils import editor
+
+            crops = [
+                (screenshot.capture_region(w.region, display=args.display).image, w.region)
+                for w in chosen
+            ]
+            compose = editor.composite_row if args.arrange == "row" else editor.composite_layout
+            data = screenshot.CaptureData(image=compose(crops))
+        else:
+            # Prefer the window's own drawable so overlapping windows don't
+            # bleed into the result; fall back to cropping the root image.
+            png = windows.capture_window_pixels(chosen.window_id, args.display)
+            if args.keep_alpha:
+                # Real transparency only exists in the ARGB drawable; the
+                # root image is already composited flat. Fail loudly rather
+                # than silently handing back opaque pixels.
+                if png is None:
+                    raise CaptureError(
+                        "cannot preserve alpha: window drawable capture "
+                        "unavailable (is ImageMagick installed?)"
+                    )
+                if (args.format or "png").lower() in ("jpg", "jpeg", "pdf"):
+                    raise CaptureError("--keep-alpha needs a format with transparency")
+            if png:
+                import io
+
+                from PIL import Image
+
+                image = screenshot.normalize_image(Image.open(io.BytesIO(png)))
+                data = screenshot.CaptureData(
+                    image=image.convert("RGBA"), region=chosen.region
+                )
+            else:
+                if chosen.desktop >= 0 and chosen.desktop != windows.current_desktop():
+                    # The root image only shows the current workspace, so
+                    # without the drawable there is nothing correct to crop.
+                    raise CaptureError(
+                        "window is on workspace %d; capturing it without "
+                        "switching needs a compositor and ImageMagick "
+                        "(or use `capture workspace %d` to switch-and-grab)"
+                        % (chosen.desktop, chosen.desktop)
+                    )
+                data = screenshot.capture_region(chosen.region, display=args.display)
+            data.metadata.update(chosen.metadata())
+            apply_window_rules(args, config, data.metadata)
+    else:
+        frozen = None
+        if args.geometry:
+            monitor = screenshot.primary_monitor(display=args.display)
+            region = resolve_region(args.geometry, monitor, config.presets())
+        else:
+            from ui.widgets import select_region_interactively
+
+            grid = int(config.get("overlay", "grid", fallback="8"))
+            # One grab serves both --freeze and Ctrl-snap; edge detection
+            # itself is deferred to the first Ctrl press inside the
+            # overlay, since most selections never use it.
+            frame = screenshot.capture_fullscreen(display=args.display)
+            if args.freeze:
+                # The overlay shows this frame instead of the live screen,
+                # so animations and videos can't change between selection
+                # and capture; the crop comes from the same frame.
+                frozen = frame
+            region = select_region_interactively(
+                grid_size=grid,
+                edge_source=frame.image,
+                theme=config.get("overlay", "theme", fallback="default"),
+                background=frozen.to_png_bytes() if frozen else None,
+            )
+            if region is None:
+                raise SelectionCancelled("selection cancelled")
+        if frozen is not None:
+            x, y, w, h = region
+            data = screenshot.CaptureData(
+                image=frozen.image.crop((x, y, x + w, y + h)), region=(x, y, w, h)
+            )
+        else:
+            data = screenshot.capture_region(region, display=args.display)
+    data.scale = screenshot.detect_scale(args.display)
+    if args.logical:
+        data = screenshot.to_logical(data)
+    if args.undo_night_light:
+        data.image = screenshot.undo_gamma(
+            data.image, screenshot.query_gamma(args.display)
+        )
+    if args.ocr_only:
+        from capture.ocr import extract_text
+        from utils.clipboard import copy_text
+
+        text = extract_text(data)
+        if args.translate:
+            from capture.ocr import translate_text
+
+            text = translate_text(text, args.translate, config)
+        from capture.ocr import record_history
+
+        record_history(text)
+        copy_text(text)
+        print(text)
+        if args.speak:
+            from capture.ocr import speak_text
+
+            speak_text(text)
+        return
+    if args.scale:
+        from utils.gpu import scale_image
+
+        data.image = scale_image(
+            data.image,
+            (data.width * args.scale // 100, data.height * args.scale // 100),
+        )
+    if args.background:
+        # Decouple capture from delivery: the parent returns as soon as the
+        # pixels are grabbed, while a detached child does the encoding,
+        # saving, and uploading and reports through a notification. Slow
+        # NFS Pictures mounts and uploads stop holding the keybinding.
+        storage.record_last_capture(
+            {
+                "target": args.target,
+                "region": data.region,
+                "output": args.output,
+                "format": args.format,
+                "scale": args.scale,
+                "quality": args.quality,
+                "to": args.to,
+            }
+        )
+        if os.fork() > 0:
+            return
+        os.setsid()
+        try:
+            results = list(deliver(data, args, config))
+            from utils.notify import notify
+
+            notify("Capture saved", "\n".join(results))
+        except Exception as exc:
+            from utils.notify import notify
+
+            notify("Capture failed", str(exc))
+        finally:
+            os._exit(0)
+    results = []
+    for result in deliver(data, args, config):
+        results.append(result)
+        if not args.json:
+            print(result)
+    if args.snippet:
+        snippet = format_snippet(results, args.snippet, config)
+        if snippet:
+            print(snippet)
+    if args.json:
+        import json
+
+        print(json.dumps({"cancelled": False, "results": results}))
+    if config.get("notify", "enabled", fallback="yes") == "yes":
+        from utils.notify import notify
+
+        notify("Capture saved", "\n".join(results))
+    # Record the resolved region (not the raw spec) so redo hits the same pixels.
+    storage.record_last_capture(
+        {
+            "target": args.target,
+            "region": data.region,
+            "output": args.output,
+            "format": args.format,
+            "scale": args.scale,
+            "quality": args.quality,
+            "to": args.to,
+        }
+    )
+
+
+def format_snippet(results, style, config):
+    """A paste-ready Markdown/Org image link for what deliver() produced.
+
+    Prefers the uploaded URL over a local path when both exist; paths
+    inside the configured notes directory are made relative so the link
+    survives a synced vault moving between machines.
+    """
+    target = None
+    for result in results:
+        if result.startswith(("http://", "https://")):
+            target = result
+            break
+    if target is None:
+        for result in results:
+            if os.path.isabs(result):
+                target = result
+                break
+    if target is None:
+        return None
+    is_url = target.startswith(("http://", "https://"))
+    notes_dir = config.get("save", "notes_dir")
+    if not is_url and notes_dir:
+        notes_dir = os.path.expanduser(notes_dir)
+        if target.startswith(notes_dir.rstrip(os.sep) + os.sep):
+            target = os.path.relpath(target, notes_dir)
+    if style == "org":
+        return "[[%s]]" % (target if is_url else "file:" + target)
+    return "![](%s)" % target
+
+
+def deliver(data, args, config):
+    """Send a capture to every requested sink, yielding one result line each."""
+    from utils.progress import stderr_progress
+
+    # Progress lines go to stderr and only when it's a terminal, so they
+    # never pollute --json output or piped results.
+    progress = None if getattr(args, "json", False) else stderr_progress
+    sinks = (args.to or "file").split(",")
+    if getattr(args, "print_copy", False):
+        sinks.append("print")
+    for sink in sinks:
+        sink = sink.strip()
+        if sink == "file":
+            if getattr(args, "save_dialog", False) and not args.output:
+                from ui.widgets import choose_save_path
+
+                args.output = choose_save_path(
+                    storage.default_filename(args.format or "png"),
+                    directory=storage.default_save_dir(),
+                )
+                if args.output is None:
+                    raise SelectionCancelled("save dialog cancelled")
+            if getattr(args, "temp", False):
+                yield storage.save_temp_capture(data, extension=args.format or "png")
+            else:
+                yield storage.save_capture(
+                    data,
+                    args.output,
+                    extension=args.format or "png",
+                    subdir_template=config.get("save", "subdirs"),
+                    quality=args.quality,
+                    fsync=config.get("save", "fsync", fallback="no") == "yes",
+                    progress=progress,
+                )
+        elif sink == "vault":
+            from utils.clipboard import copy_text
+
+            path, name = storage.save_to_vault(
+                data, config, extension=args.format or "png"
+            )
+            # The embed link lands on the clipboard so the screenshot can
+            # be pasted into a note immediately.
+            copy_text("![[%s]]" % name)
+            yield path
+        elif sink == "clipboard":
+            from utils.clipboard import copy_image
+
+            copy_image(data)
+            yield "copied to clipboard"
+        elif sink == "print":
+            from utils.printing import print_capture
+
+            yield print_capture(data, config)
+        elif sink.startswith("email:"):
+            from services import email_export
+
+            yield email_export.send(data, sink.split(":", 1)[1], config)
+        elif sink.startswith("webhook:"):
+            from services import webhooks
+
+            yield webhooks.post(data, sink.split(":", 1)[1], config)
+        elif sink.startswith("upload:"):
+            from services import cloud_services
+
+            yield cloud_services.upload(
+                data, sink.split(":", 1)[1], config, progress=progress
+            )
+        else:
+            raise CaptureError("unknown sink %r" % sink)
+
+
+def cmd_ocr(args, config):
+    from capture import ocr
+
+    if args.path == "history":
+        return cmd_ocr_history(args, config)
+    if args.path == "live":
+        from ui.widgets import run_live_ocr
+
+        run_live_ocr()
+        return
+    image = ocr.load_image(args.path)
+    if args.table:
+        print(ocr.extract_table(image, lang=args.lang, fmt=args.table))
+        return
+    text = ocr.extract_text(image, lang=args.lang, profile=args.profile)
+    if args.translate:
+        text = ocr.translate_text(text, args.translate, config)
+    ocr.record_history(text)
+    print(text)
+    if args.open_links:
+        urls = ocr.find_urls(text)
+        if not urls:
+            raise CaptureError("no URLs found in the recognized text")
+        ocr.open_link(urls[0])
+        for url in urls[1:]:
+            print("also found: %s" % url, file=sys.stderr)
+    if args.speak:
+        ocr.speak_text(text)
+
+
+def cmd_ocr_history(args, config):
+    from capture import ocr
+
+    history = ocr.load_history()
+    if args.rest and args.rest[0] == "copy":
+        from utils.clipboard import copy_text
+
+        try:
+            entry = history[int(args.rest[1]) - 1]
+        except (IndexError, ValueError):
+            raise CaptureError(
+                "no such history entry (have %d, numbered from 1)" % len(history)
+            )
+        copy_text(entry)
+        return
+    if not history:
+        print("no OCR history yet")
+        return
+    for number, entry in enumerate(history, 1):
+        preview = " ".join(entry.split())
+        if len(preview) > 70:
+            preview = preview[:69] + "…"
+        print("%2d  %s" % (number, preview))
+
+
+def _parse_age(spec):
+    """Seconds for a '7d' / '48h' / '90m' style age spec."""
+    units = {"d": 86400, "h": 3600, "m": 60, "s": 1}
+    try:
+        if spec and spec[-1] in units:
+            return float(spec[:-1]) * units[spec[-1]]
+        return float(spec)
+    except ValueError:
+        raise CaptureError("bad --since value %r (use e.g. 7d, 48h, 90m)" % spec)
+
+
+def cmd_history(args, config):
+    """List recent captures, or render them into a contact sheet.
+
+    Works off the files in the save directory rather than separate state,
+    so captures made before the feature existed (and ones moved in by
+    hand) show up too.
+    """
+    import time
+
+    cutoff = time.time() - _parse_age(args.since)
+    root = storage.default_save_dir()
+    entries = []
+    for dirpath, _dirs, names in os.walk(root):
+        for name in names:
+            if not name.lower().endswith((".png", ".jpg", ".jpeg", ".webp")):
+                continue
+            path = os.path.join(dirpath, name)
+            try:
+                mtime = os.path.getmtime(path)
+            except OSError:
+                continue
+            if mtime >= cutoff:
+                entries.append((mtime, path))
+    entries.sort()
+    if args.action == "list":
+        for mtime, path in entries:
+            print(time.strftime("%Y-%m-%d %H:%M", time.localtime(mtime)), path)
+        return
+    if not entries:
+        raise CaptureError("no captures in the last %s under %s" % (args.since, root))
+    from PIL import Image
+
+    from utils.editor import contact_sheet
+
+    labelled = []
+    for mtime, path in entries:
+        try:
+            image = Image.open(path)
+        except OSError:
+            continue  # half-written or foreign file; leave it off the sheet
+        labelled.append(
+            (image, time.strftime("%Y-%m-%d %H:%M", time.localtime(mtime)))
+        )
+    if not labelled:
+        raise CaptureError("no readable captures in the last %s" % args.since)
+    sheet = contact_sheet(labelled, columns=args.columns)
+    path = args.output or os.path.join(
+        root, time.strftime("Contact sheet %Y-%m-%d.png")
+    )
+    sheet.save(path)
+    print(path)
+
+
+def cmd_remote(args, config):
+    """Capture on a remote machine over SSH and deliver the result locally.
+
+    The remote side writes into its temp capture dir and prints the path;
+    we stream the bytes back (removing the remote copy) and hand them to
+    the local file/clipboard sinks, so grabbing a screenshot of a lab
+    machine is one command from the admin's desk.
+    """
+    import subprocess
+
+    remote_argv = list(args.rest) or ["capture", "screen"]
+    try:
+        listing = subprocess.run(
+            ["ssh", args.host, "openshotx"] + remote_argv + ["--temp"],
+            capture_output=True,
+            text=True,
+            check=True,
+        )
+    except OSError:
+        raise CaptureError("ssh is not installed")
+    except subprocess.CalledProcessError as exc:
+        detail = exc.stderr.strip().splitlines()[-1] if exc.stderr.strip() else "no detail"
+        raise CaptureError("remote capture failed: %s" % detail)
+    lines = [line for line in listing.stdout.splitlines() if line.strip()]
+    if not lines:
+        raise CaptureError("remote capture produced no output path")
+    remote_path = lines[-1].strip()
+    try:
+        fetched = subprocess.run(
+            ["ssh", args.host, "cat '%s' && rm -f '%s'" % (remote_path, remote_path)],
+            capture_output=True,
+            check=True,
+        )
+    except subprocess.CalledProcessError:
+        raise CaptureError("could not fetch %s from %s" % (remote_path, args.host))
+    import io
+
+    from PIL import Image
+
+    try:
+        image = screenshot.normalize_image(Image.open(io.BytesIO(fetched.stdout)))
+    except OSError:
+        raise CaptureError("remote did not return a recognizable image")
+    data = screenshot.CaptureData(image=image, metadata={"remote_host": args.host})
+    if (args.to or "file") == "clipboard":
+        from utils.clipboard import copy_image
+
+        copy_image(data)
+        print("copied to clipboard")
+    else:
+        print(storage.save_capture(data, args.output))
+
+
+def cmd_doctor(args, config):
+    import json
+
+    from utils.doctor import run_checks
+
+    checks = run_checks()
+    if args.json:
+        print(json.dumps(checks, indent=2))
+    else:
+        for check in checks:
+            status = "ok  " if check["ok"] else "FAIL"
+            print("%s %s (%s)" % (status, check["name"], check["detail"]))
+            if not check["ok"]:
+                print("     hint: %s" % check["hint"])
+    if not all(check["ok"] for check in checks):
+        sys.exit(1)
+
+
+def _graceful_termination():
+    """Translate SIGTERM into KeyboardInterrupt.
+
+    Recording and interval modes already clean up properly on Ctrl-C
+    (segments spliced, queues flushed, state removed); routing SIGTERM
+    through the same path means `kill`, session logout, and systemd stop
+    never leave corrupt outputs behind either.
+    """
+    import signal
+
+    def raise_interrupt(signum, frame):
+        raise KeyboardInterrupt
+
+    signal.signal(signal.SIGTERM, raise_interrupt)
+
+
+def _suffixed(path, tag):
+    base, ext = os.path.splitext(path)
+    return base + tag + ext
+
+
+def cmd_record(args, config):
+    from capture import recorder
+
+    if args.action == "stop":
+        output = recorder.stop_active()
+        if output:
+            print(output)
+        return
+    if args.action in ("pause", "resume"):
+        recorder.toggle_pause_active()
+        return
+    if args.action == "trim":
+        if not args.files:
+            raise CaptureError("record trim requires an input file")
+        source = args.files[0]
+        output = args.files[1] if len(args.files) > 1 else _suffixed(source, ".trimmed")
+        print(recorder.trim(source, output, start=args.start, end=args.end))
+        return
+    if args.action == "convert":
+        if len(args.files) < 2:
+            raise CaptureError("record convert requires input and output files")
+        print(recorder.convert(args.files[0], args.files[1]))
+        return
+    region = None
+    follow_window = None
+    if args.action == "window":
+        from capture import windows
+
+        info = windows.active_window()
+        region = info.region
+        if args.follow:
+            follow_window = info.window_id
+    elif args.geometry:
+        monitor = screenshot.primary_monitor()
+        region = resolve_region(args.geometry, monitor, config.presets()).as_tuple()
+    output = args.output or os.path.join(
+        storage.default_save_dir(), storage.default_filename("mp4")
+    )
+    # dirname is "" for a bare `-o out.mp4`; the current directory exists.
+    os.makedirs(os.path.dirname(output) or ".", exist_ok=True)
+    rec = recorder.Recorder(
+        region=region,
+        output=output,
+        vfr=config.get("record", "vfr", fallback="yes") == "yes",
+    )
+    rec.start()
+    follower = None
+    if follow_window is not None:
+        follower = recorder.WindowFollower(rec, follow_window)
+        follower.start()
+    import signal
+
+    signal.signal(signal.SIGUSR1, lambda signum, frame: rec.toggle_pause())
+    _graceful_termination()
+    print("recording to %s (Ctrl-C or `openshotx record stop` to finish)" % output)
+    webcam = None
+    if args.webcam:
+        monitor = screenshot.primary_monitor()
+        webcam = recorder.WebcamBubble(
+            device=config.get("record", "webcam_device", fallback="/dev/video0"),
+            corner=args.webcam_corner,
+            size=int(config.get("record", "webcam_size", fallback="240")),
+        )
+        webcam.start(monitor.width, monitor.height)
+    visualizer = None
+    if args.show_input and not args.no_indicator:
+        from ui.input_visualizer import InputVisualizer
+
+        visualizer = InputVisualizer()
+        visualizer.start()
+    try:
+        if args.no_indicator:
+            rec.wait()
+        else:
+            from ui.widgets import show_recording_indicator
+
+            show_recording_indicator(rec.stop, on_pause=rec.toggle_pause)
+    except KeyboardInterrupt:
+        pass
+    if follower is not None:
+        follower.stop()
+    if visualizer is not None:
+        visualizer.stop()
+    if webcam is not None:
+        webcam.stop()
+    from utils.progress import stderr_progress
+
+    rec.stop(progress=stderr_progress)
+    print(output)
+
+
+def cmd_config(args, config):
+    if args.action == "show":
+        print(config.dump())
+    elif args.action == "path":
+        print(config.path)
+    elif args.action == "set":
+        if not args.key or args.value is None:
+            raise CaptureError("config set requires a section.key and a value")
+        try:
+            config.set_value(args.key, args.value)
+        except ValueError as exc:
+            raise CaptureError(str(exc))
+    elif args.action == "validate":
+        problems = config.validate()
+        for problem in problems:
+            print(problem)
+        if problems:
+            sys.exit(1)
+        print("configuration OK")
+
+
+def cmd_redo(args, config):
+    """Repeat the previous capture with the same parameters.
+
+    Replays the resolved region and the recorded delivery options, so a
+    redo after a clipboard webp capture produces another clipboard webp
+    instead of a default PNG file.
+    """
+    last = storage.load_last_capture()
+    if last is None:
+        raise CaptureError("no previous capture to redo")
+    if last.get("region"):
+        data = screenshot.capture_region(tuple(last["region"]))
+    else:
+        data = screenshot.capture_fullscreen()
+    if last.get("scale"):
+        from utils.gpu import scale_image
+
+        data.image = scale_image(
+            data.image,
+            (data.width * last["scale"] // 100, data.height * last["scale"] // 100),
+        )
+    replay = argparse.Namespace(
+        output=last.get("output"),
+        format=last.get("format"),
+        quality=last.get("quality"),
+        to=last.get("to"),
+    )
+    for result in deliver(data, replay, config):
+        print(result)
+
+
+def run_gui():
+    from PyQt5.QtWidgets import QApplication
+    from ui.main_window import MainWindow
 
-def main():
     app = QApplication(sys.argv)  # Create an application object
     window = MainWindow()         # Create a window object
     window.show()                 # Display the window
     sys.exit(app.exec_())         # Start the application's event loop
 
+
+def main():
+    argv = sys.argv[1:]
+    # Act as a drop-in flameshot replacement when invoked under its name or
+    # through the explicit compatibility entry point.
+    if os.path.basename(sys.argv[0]) == "flameshot":
+        from utils.compat import from_flameshot
+
+        argv = from_flameshot(argv)
+    elif argv and argv[0] == "flameshot-compat":
+        from utils.compat import from_flameshot
+
+        argv = from_flameshot(argv[1:])
+    elif os.path.basename(sys.argv[0]) in ("maim", "scrot"):
+        from utils.compat import from_maim
+
+        argv = from_maim(argv)
+    elif argv and argv[0] == "maim-compat":
+        from utils.compat import from_maim
+
+        argv = from_maim(argv[1:])
+    parser = build_parser()
+    args = parser.parse_args(argv)
+    sys.exit(run(args))
+
+
+def run(args):
+    """Dispatch a parsed command line, returning a process exit code.
+
+    Every failure path funnels through here so users get one friendly error
+    line and a meaningful exit code instead of a traceback; set
+    OPENSHOTX_DEBUG=1 to see the full backtrace when hacking on the tool.
+    """
+    if args.command is None:
+        run_gui()
+        return 0
+    config = load_config()
+    storage.clean_expired_temp()
+    try:
+        if args.command == "capture":
+            import time
+
+            from utils import metrics, state
+
+            # One lock per capture type: parallel area captures queue, but
+            # an area capture never blocks a scripted monitor grab.
+            lock = state.acquire_lock("capture-" + args.target)
+            started = time.monotonic()
+            try:
+                cmd_capture(args, config)
+            except CaptureError:
+                metrics.record_capture(False, time.monotonic() - started)
+                raise
+            else:
+                metrics.record_capture(True, time.monotonic() - started)
+            finally:
+                lock.close()
+        elif args.command == "redo":
+            cmd_redo(args, config)
+        elif args.command == "ocr":
+            cmd_ocr(args, config)
+        elif args.command == "config":
+            cmd_config(args, config)
+        elif args.command == "monitors":
+            for monitor in screenshot.list_monitors(args.display):
+                flags = []
+                if monitor.primary:
+                    flags.append("primary")
+                if monitor.rotation != "normal":
+                    flags.append(monitor.rotation)
+                print(
+                    "%-12s %dx%d+%d+%d%s"
+                    % (
+                        monitor.name,
+                        monitor.width,
+                        monitor.height,
+                        monitor.x,
+                        monitor.y,
+                        (" (" + ", ".join(flags) + ")") if flags else "",
+                    )
+                )
+        elif args.command == "doctor":
+            cmd_doctor(args, config)
+        elif args.command == "record":
+            cmd_record(args, config)
+        elif args.command == "portal-backend":
+            from services import portal_backend
+
+            portal_backend.run()
+        elif args.command == "bench":
+            from utils.bench import run_benchmarks
+
+            for name, seconds in run_benchmarks():
+                if seconds is None:
+                    print("%-24s skipped" % name)
+                else:
+                    print("%-24s %8.1f ms" % (name, seconds * 1000))
+        elif args.command == "history":
+            cmd_history(args, config)
+        elif args.command == "remote":
+            cmd_remote(args, config)
+        elif args.command == "serve":
+            from services import stdio_server
+
+            stdio_server.run()
+        elif args.command == "status":
+            from utils import metrics
+
+            if args.serve:
+                metrics.serve(args.serve)
+            else:
+                m = metrics.summary()
+                captures = m.get("captures", 0)
+                print("captures:  %d" % captures)
+                print("failures:  %d" % m.get("failures", 0))
+                if captures:
+                    print(
+                        "mean time: %.2fs" % (m.get("latency_sum", 0.0) / captures)
+                    )
+                if "last_capture" in m:
+                    import time
+
+                    print(
+                        "last:      %s"
+                        % time.strftime(
+                            "%Y-%m-%d %H:%M:%S", time.localtime(m["last_capture"])
+                        )
+                    )
+        elif args.command == "state":
+            from utils import state
+
+            if args.action == "reset":
+                state.reset()
+            else:
+                print(state.STATE_DIR)
+    except SelectionCancelled as exc:
+        if getattr(args, "json", False):
+            import json
+
+            print(json.dumps({"cancelled": True}))
+        else:
+            print(str(exc), file=sys.stderr)
+        return 2
+    except CaptureError as exc:
+        print("error: %s" % exc, file=sys.stderr)
+        return 1
+    except KeyboardInterrupt:
+        print("interrupted", file=sys.stderr)
+        return 130
+    except Exception as exc:  # noqa: BLE001 - last-resort friendliness
+        if os.environ.get("OPENSHOTX_DEBUG"):
+            raise
+        print(
+            "unexpected error: %s (set OPENSHOTX_DEBUG=1 for a backtrace)" % exc,
+            file=sys.stderr,
+        )
+        return 1
+    return 0
+
+
 if __name__ == '__main__':
     main()
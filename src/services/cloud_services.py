@@ -0,0 +1,176 @@
+import base64
+import json
+import urllib.parse
+import urllib.request
+
+from capture.screenshot import CaptureError
+
+IMGUR_ENDPOINT = "https://api.imgur.com/3/image"
+
+
+class UploadError(CaptureError):
+    pass
+
+
+def upload(capture, service, config, progress=None):
+    """Upload a capture to the named service and return the shareable URL.
+
+    progress, when given, is called with (fraction, message) so uploads over
+    slow links don't look like a hang.
+    """
+    if service == "imgur":
+        return _upload_imgur(capture, config, progress=progress)
+    if service.startswith("github:"):
+        return _upload_github_issue(
+            capture, service.split(":", 1)[1], config, progress=progress
+        )
+    if service.startswith("jira:"):
+        return _upload_jira_issue(
+            capture, service.split(":", 1)[1], config, progress=progress
+        )
+    raise UploadError("unknown upload service %r" % service)
+
+
+def _upload_imgur(capture, config, progress=None):
+    client_id = config.get("upload.imgur", "client_id")
+    if not client_id:
+        raise UploadError("set client_id under [upload.imgur] in the config")
+    if progress:
+        progress(0.0, "encoding for imgur")
+    payload = urllib.parse.urlencode(
+        {"image": base64.b64encode(capture.to_png_bytes()), "type": "base64"}
+    ).encode()
+    if progress:
+        progress(0.3, "uploading %dkB to imgur" % (len(payload) // 1024))
+    request = urllib.request.Request(
+        IMGUR_ENDPOINT,
+        data=payload,
+        headers={"Authorization": "Client-ID " + client_id},
+    )
+    try:
+        with urllib.request.urlopen(request, timeout=30) as response:
+            body = json.load(response)
+        link = body["data"]["link"]
+    except (OSError, KeyError, ValueError) as exc:
+        raise UploadError("imgur upload failed: %s" % exc)
+    if progress:
+        progress(1.0, "uploaded to imgur")
+    return link
+
+
+def _api_json(request, error_prefix):
+    try:
+        with urllib.request.urlopen(request, timeout=30) as response:
+            return json.load(response)
+    except (OSError, ValueError) as exc:
+        raise UploadError("%s: %s" % (error_prefix, exc))
+
+
+def _upload_github_issue(capture, issue, config, progress=None):
+    """Attach a capture to a GitHub issue and return the image URL.
+
+    GitHub's REST API has no attachment endpoint, so the image is committed
+    under screenshots/ in the issue's repository via the contents API and a
+    comment embedding it is posted — the same shape drag-and-drop in the
+    web UI produces. Needs token and repo under [upload.github]; the
+    service spec carries the issue number, e.g. --to upload:github:123.
+    """
+    import time
+
+    token = config.get("upload.github", "token")
+    repo = config.get("upload.github", "repo")
+    if not token or not repo:
+        raise UploadError("set token and repo under [upload.github] in the config")
+    if not issue.isdigit():
+        raise UploadError("github issue must be a number, got %r" % issue)
+    headers = {
+        "Authorization": "token " + token,
+        "Accept": "application/vnd.github+json",
+    }
+    name = time.strftime("screenshots/issue-%s-%%Y%%m%%d%%H%%M%%S.png" % issue)
+    if progress:
+        progress(0.2, "committing image to %s" % repo)
+    body = _api_json(
+        urllib.request.Request(
+            "https://api.github.com/repos/%s/contents/%s" % (repo, name),
+            data=json.dumps(
+                {
+                    "message": "Attach screenshot for #%s" % issue,
+                    "content": base64.b64encode(capture.to_png_bytes()).decode(),
+                }
+            ).encode(),
+            headers=headers,
+            method="PUT",
+        ),
+        "github upload failed",
+    )
+    try:
+        url = body["content"]["download_url"]
+    except (KeyError, TypeError):
+        raise UploadError("github upload failed: unexpected response")
+    if progress:
+        progress(0.8, "commenting on #%s" % issue)
+    _api_json(
+        urllib.request.Request(
+            "https://api.github.com/repos/%s/issues/%s/comments" % (repo, issue),
+            data=json.dumps({"body": "![screenshot](%s)" % url}).encode(),
+            headers=headers,
+        ),
+        "github comment failed",
+    )
+    if progress:
+        progress(1.0, "attached to #%s" % issue)
+    return url
+
+
+def _upload_jira_issue(capture, issue_key, config, progress=None):
+    """Attach a capture to a Jira issue and return the attachment URL.
+
+    Uses the standard attachments endpoint with a multipart body built by
+    hand (urllib has no multipart support). Needs url, user, and token
+    under [upload.jira]; the service spec carries the issue key, e.g.
+    --to upload:jira:PROJ-123.
+    """
+    import time
+    import uuid
+
+    base_url = config.get("upload.jira", "url")
+    user = config.get("upload.jira", "user")
+    token = config.get("upload.jira", "token")
+    if not base_url or not user or not token:
+        raise UploadError("set url, user, and token under [upload.jira] in the config")
+    boundary = uuid.uuid4().hex
+    name = time.strftime("screenshot-%Y%m%d%H%M%S.png")
+    payload = b"".join(
+        [
+            ("--%s\r\n" % boundary).encode(),
+            (
+                'Content-Disposition: form-data; name="file"; filename="%s"\r\n' % name
+            ).encode(),
+            b"Content-Type: image/png\r\n\r\n",
+            capture.to_png_bytes(),
+            ("\r\n--%s--\r\n" % boundary).encode(),
+        ]
+    )
+    auth = base64.b64encode(("%s:%s" % (user, token)).encode()).decode()
+    if progress:
+        progress(0.3, "uploading %dkB to %s" % (len(payload) // 1024, issue_key))
+    body = _api_json(
+        urllib.request.Request(
+            "%s/rest/api/2/issue/%s/attachments" % (base_url.rstrip("/"), issue_key),
+            data=payload,
+            headers={
+                "Authorization": "Basic " + auth,
+                "X-Atlassian-Token": "no-check",
+                "Content-Type": "multipart/form-data; boundary=" + boundary,
+            },
+        ),
+        "jira upload failed",
+    )
+    try:
+        url = body[0]["content"]
+    except (KeyError, IndexError, TypeError):
+        raise UploadError("jira upload failed: unexpected response")
+    if progress:
+        progress(1.0, "attached to %s" % issue_key)
+    return url
@@ -0,0 +1,80 @@
+import subprocess
+import time
+
+from capture.screenshot import CaptureError
+from utils import storage
+
+
+class EmailError(CaptureError):
+    pass
+
+
+def send(capture, recipient, config):
+    """Email a capture to the given address.
+
+    With an [email] section configured the message goes straight out over
+    SMTP:
+
+        [email]
+        host = smtp.example.com
+        port = 587
+        user = me@example.com
+        password = app-password
+        from = me@example.com
+
+    Without one, the default mail client is opened through xdg-email with
+    the image attached, so the user can finish the mail by hand.
+    """
+    if not recipient:
+        raise EmailError("email sink needs a recipient, e.g. --to email:boss@corp.com")
+    host = config.get("email", "host")
+    if host:
+        _send_smtp(capture, recipient, config, host)
+        return "emailed to %s" % recipient
+    path = storage.save_temp_capture(capture)
+    try:
+        subprocess.run(
+            [
+                "xdg-email",
+                "--subject", "Screenshot",
+                "--attach", path,
+                recipient,
+            ],
+            check=True,
+        )
+    except (OSError, subprocess.CalledProcessError):
+        raise EmailError(
+            "no [email] section configured and xdg-email is not available"
+        )
+    return "opened mail client for %s" % recipient
+
+
+def _send_smtp(capture, recipient, config, host):
+    import smtplib
+    from email.message import EmailMessage
+
+    port = int(config.get("email", "port", fallback="587"))
+    user = config.get("email", "user")
+    sender = config.get("email", "from", fallback=user)
+    if not sender:
+        raise EmailError("set from (or user) under [email] in the config")
+    message = EmailMessage()
+    message["Subject"] = "Screenshot"
+    message["From"] = sender
+    message["To"] = recipient
+    message.set_content("Screenshot attached.")
+    message.add_attachment(
+        capture.to_png_bytes(),
+        maintype="image",
+        subtype="png",
+        filename=time.strftime("screenshot-%Y%m%d%H%M%S.png"),
+    )
+    try:
+        with smtplib.SMTP(host, port, timeout=30) as smtp:
+            if config.get("email", "tls", fallback="yes") == "yes":
+                smtp.starttls()
+            if user:
+                smtp.login(user, config.get("email", "password", fallback=""))
+            smtp.send_message(message)
+    except (OSError, smtplib.SMTPException) as exc:
+        raise EmailError("sending mail failed: %s" % exc)
@@ -0,0 +1,137 @@
+import os
+import time
+
+from PyQt5.QtCore import Q_CLASSINFO, QObject, pyqtSlot
+from PyQt5.QtDBus import (
+    QDBusAbstractAdaptor,
+    QDBusConnection,
+    QDBusMessage,
+)
+
+from capture import screenshot
+from capture.screenshot import CaptureError
+from utils import storage
+
+SERVICE_NAME = "org.freedesktop.impl.portal.desktop.openshotx"
+OBJECT_PATH = "/org/freedesktop/portal/desktop"
+
+# Portal response codes.
+RESPONSE_SUCCESS = 0
+RESPONSE_CANCELLED = 1
+RESPONSE_FAILED = 2
+
+# Exit after this many minutes without a request, so a DBus/socket-activated
+# user service doesn't linger forever; 0 keeps the daemon running.
+IDLE_EXIT_MIN = float(os.environ.get("OPENSHOTX_IDLE_EXIT_MIN", "0"))
+
+
+def _sd_notify(message):
+    """Report state to systemd when running as a Type=notify user service.
+
+    Silently a no-op outside systemd (no NOTIFY_SOCKET in the environment),
+    so the same code path serves manual runs and socket activation.
+    """
+    import socket
+
+    path = os.environ.get("NOTIFY_SOCKET")
+    if not path:
+        return
+    if path.startswith("@"):  # abstract socket namespace
+        path = "\0" + path[1:]
+    try:
+        sock = socket.socket(socket.AF_UNIX, socket.SOCK_DGRAM)
+        try:
+            sock.sendto(message.encode(), path)
+        finally:
+            sock.close()
+    except OSError:
+        pass
+
+
+class ScreenshotAdaptor(QDBusAbstractAdaptor):
+    """org.freedesktop.impl.portal.Screenshot provider.
+
+    Lets OpenShotX serve as the portal backend on minimalist compositors, so
+    every portal screenshot request goes through our selector UI instead of
+    whatever the desktop happens to ship.
+    """
+
+    Q_CLASSINFO("D-Bus Interface", "org.freedesktop.impl.portal.Screenshot")
+
+    def __init__(self, parent):
+        super().__init__(parent)
+
+    @pyqtSlot(QDBusMessage)
+    def Screenshot(self, message):
+        self.parent().last_request = time.monotonic()
+        handle, app_id, parent_window, options = message.arguments()
+        interactive = bool(options.get("interactive", False))
+        try:
+            if interactive:
+                from ui.widgets import select_region_interactively
+
+                region = select_region_interactively()
+                if region is None:
+                    self._reply(message, RESPONSE_CANCELLED, {})
+                    return
+                data = screenshot.capture_region(region)
+            else:
+                data = screenshot.capture_fullscreen()
+            path = storage.save_temp_capture(data)
+        except CaptureError:
+            self._reply(message, RESPONSE_FAILED, {})
+            return
+        self._reply(message, RESPONSE_SUCCESS, {"uri": "file://" + path})
+
+    def _reply(self, message, response, results):
+        reply = message.createReply([response, results])
+        QDBusConnection.sessionBus().send(reply)
+
+
+class PortalBackend(QObject):
+    def __init__(self):
+        super().__init__()
+        self.adaptor = ScreenshotAdaptor(self)
+        self.last_request = time.monotonic()
+
+
+def run():
+    """Register on the session bus and serve portal screenshot requests."""
+    import sys
+
+    from PyQt5.QtWidgets import QApplication
+
+    import signal
+
+    from PyQt5.QtCore import QTimer
+
+    app = QApplication.instance() or QApplication(sys.argv)
+    bus = QDBusConnection.sessionBus()
+    backend = PortalBackend()
+    if not bus.registerService(SERVICE_NAME):
+        raise CaptureError("could not register %s (already running?)" % SERVICE_NAME)
+    bus.registerObject(OBJECT_PATH, backend, QDBusConnection.ExportAdaptors)
+    print("serving %s on %s" % (SERVICE_NAME, OBJECT_PATH))
+    # Quit cleanly on SIGINT/SIGTERM so the bus name is released instead of
+    # lingering until the bus notices the dead peer. The timer wakes the
+    # interpreter periodically; Python signal handlers can't run while Qt
+    # sits in its C event loop.
+    signal.signal(signal.SIGINT, lambda *_: app.quit())
+    signal.signal(signal.SIGTERM, lambda *_: app.quit())
+
+    def tick():
+        # Besides waking the interpreter for signal delivery, enforce the
+        # idle-exit deadline for activated services.
+        if (
+            IDLE_EXIT_MIN > 0
+            and time.monotonic() - backend.last_request > IDLE_EXIT_MIN * 60
+        ):
+            app.quit()
+
+    wake = QTimer()
+    wake.timeout.connect(tick)
+    wake.start(500)
+    _sd_notify("READY=1")
+    app.exec_()
+    _sd_notify("STOPPING=1")
+    bus.unregisterService(SERVICE_NAME)
@@ -0,0 +1,93 @@
+import json
+import sys
+
+from capture import screenshot
+from capture.screenshot import CaptureError
+from utils import storage
+
+# Editor integration protocol: newline-delimited JSON-RPC 2.0 over
+# stdin/stdout. A plugin spawns `openshotx serve --stdio`, writes one
+# request per line, and reads one response per line — no sockets, no
+# framing headers, nothing to clean up when the editor exits.
+#
+#   {"jsonrpc": "2.0", "id": 1, "method": "capture", "params": {"target": "screen"}}
+#   {"jsonrpc": "2.0", "id": 1, "result": {"path": "/tmp/.../cap.png"}}
+
+PARSE_ERROR = -32700
+METHOD_NOT_FOUND = -32601
+INTERNAL_ERROR = -32000
+
+
+def _capture(params):
+    target = params.get("target", "screen")
+    geometry = params.get("geometry")
+    if geometry:
+        from config import load_config
+        from utils.geometry import resolve_region
+
+        monitor = screenshot.primary_monitor()
+        region = resolve_region(geometry, monitor, load_config().presets())
+        data = screenshot.capture_region(region)
+    elif target == "screen":
+        data = screenshot.capture_fullscreen()
+    else:
+        raise CaptureError("stdio capture supports target 'screen' or a geometry")
+    path = storage.save_temp_capture(data)
+    storage.record_last_capture(
+        {"target": target, "region": data.region, "output": path}
+    )
+    return {"path": path}
+
+
+def _ocr(params):
+    from capture import ocr
+
+    path = params.get("path")
+    if path is None:
+        last = storage.load_last_capture() or {}
+        path = last.get("output")
+    if not path:
+        raise CaptureError("no path given and no previous capture to read")
+    return {"text": ocr.extract_text(ocr.load_image(path), lang=params.get("lang", "eng"))}
+
+
+def _last_path(params):
+    last = storage.load_last_capture() or {}
+    return {"path": last.get("output")}
+
+
+METHODS = {
+    "capture": _capture,
+    "ocr": _ocr,
+    "last-path": _last_path,
+}
+
+
+def run():
+    """Serve requests until stdin closes."""
+    for line in sys.stdin:
+        line = line.strip()
+        if not line:
+            continue
+        request_id = None
+        try:
+            request = json.loads(line)
+            request_id = request.get("id")
+            method = METHODS.get(request.get("method"))
+            if method is None:
+                response = {
+                    "error": {
+                        "code": METHOD_NOT_FOUND,
+                        "message": "unknown method %r" % request.get("method"),
+                    }
+                }
+            else:
+                response = {"result": method(request.get("params") or {})}
+        except ValueError:
+            response = {"error": {"code": PARSE_ERROR, "message": "invalid JSON"}}
+        except CaptureError as exc:
+            response = {"error": {"code": INTERNAL_ERROR, "message": str(exc)}}
+        response["jsonrpc"] = "2.0"
+        response["id"] = request_id
+        sys.stdout.write(json.dumps(response) + "\n")
+        sys.stdout.flush()
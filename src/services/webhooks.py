@@ -0,0 +1,189 @@
+import json
+import time
+import urllib.parse
+import urllib.request
+import uuid
+
+from capture.screenshot import CaptureError
+
+
+class WebhookError(CaptureError):
+    pass
+
+
+def post(capture, name, config):
+    """Post a capture to a configured chat destination.
+
+    Destinations live under [webhook.<name>] sections with a `type` of
+    discord, slack, or matrix plus the service-specific keys documented on
+    each poster below. With `ocr = yes` in the section, the recognized text
+    goes along as the message body — triage channels get a searchable
+    message instead of an opaque image.
+    """
+    section = "webhook." + name
+    kind = config.get(section, "type")
+    if kind is None:
+        raise WebhookError("no [%s] section in the config" % section)
+    text = None
+    if config.get(section, "ocr", fallback="no") == "yes":
+        from capture.ocr import extract_text
+
+        text = extract_text(capture)
+    posters = {"discord": _post_discord, "slack": _post_slack, "matrix": _post_matrix}
+    poster = posters.get(kind)
+    if poster is None:
+        raise WebhookError(
+            "unknown webhook type %r (choose from %s)" % (kind, ", ".join(sorted(posters)))
+        )
+    poster(capture, section, config, text)
+    return "posted to %s" % name
+
+
+def _filename():
+    return time.strftime("screenshot-%Y%m%d%H%M%S.png")
+
+
+def _request(request, error_prefix):
+    try:
+        with urllib.request.urlopen(request, timeout=30) as response:
+            return response.read()
+    except OSError as exc:
+        raise WebhookError("%s: %s" % (error_prefix, exc))
+
+
+def _multipart(fields, file_field, filename, file_bytes):
+    """A (body, content_type) multipart form; urllib has no builtin for it."""
+    boundary = uuid.uuid4().hex
+    parts = []
+    for key, value in fields.items():
+        parts.append(("--%s\r\n" % boundary).encode())
+        parts.append(
+            ('Content-Disposition: form-data; name="%s"\r\n\r\n%s\r\n' % (key, value)).encode()
+        )
+    parts.append(("--%s\r\n" % boundary).encode())
+    parts.append(
+        (
+            'Content-Disposition: form-data; name="%s"; filename="%s"\r\n'
+            % (file_field, filename)
+        ).encode()
+    )
+    parts.append(b"Content-Type: image/png\r\n\r\n")
+    parts.append(file_bytes)
+    parts.append(("\r\n--%s--\r\n" % boundary).encode())
+    return b"".join(parts), "multipart/form-data; boundary=" + boundary
+
+
+def _post_discord(capture, section, config, text):
+    """Discord webhook: multipart upload straight to the webhook URL."""
+    url = config.get(section, "url")
+    if not url:
+        raise WebhookError("set url under [%s] in the config" % section)
+    fields = {}
+    if text:
+        fields["content"] = text[:2000]  # discord message limit
+    body, content_type = _multipart(fields, "file", _filename(), capture.to_png_bytes())
+    _request(
+        urllib.request.Request(url, data=body, headers={"Content-Type": content_type}),
+        "discord post failed",
+    )
+
+
+def _post_slack(capture, section, config, text):
+    """Slack: the external-upload flow (files.upload was retired in 2025).
+
+    Three steps with a bot token: files.getUploadURLExternal reserves an
+    upload slot, the PNG bytes go to the returned URL, and
+    files.completeUploadExternal shares the file into the channel. The
+    configured channel must be a channel ID (C…), not a #name.
+    """
+    token = config.get(section, "token")
+    channel = config.get(section, "channel")
+    if not token or not channel:
+        raise WebhookError("set token and channel under [%s] in the config" % section)
+    payload = capture.to_png_bytes()
+    auth = {"Authorization": "Bearer " + token}
+    params = urllib.parse.urlencode({"filename": _filename(), "length": len(payload)})
+    reserved = _slack_result(
+        urllib.request.Request(
+            "https://slack.com/api/files.getUploadURLExternal?" + params,
+            headers=auth,
+        )
+    )
+    upload_url = reserved.get("upload_url")
+    file_id = reserved.get("file_id")
+    if not upload_url or not file_id:
+        raise WebhookError("slack post failed: no upload slot in response")
+    body, content_type = _multipart({}, "file", _filename(), payload)
+    _request(
+        urllib.request.Request(
+            upload_url, data=body, headers={"Content-Type": content_type}
+        ),
+        "slack upload failed",
+    )
+    complete = {"files": [{"id": file_id, "title": _filename()}], "channel_id": channel}
+    if text:
+        complete["initial_comment"] = text
+    _slack_result(
+        urllib.request.Request(
+            "https://slack.com/api/files.completeUploadExternal",
+            data=json.dumps(complete).encode(),
+            headers=dict(auth, **{"Content-Type": "application/json"}),
+        )
+    )
+
+
+def _slack_result(request):
+    """Run a Slack Web API call and unwrap its JSON, checking the ok flag."""
+    response = _request(request, "slack post failed")
+    try:
+        result = json.loads(response)
+    except ValueError:
+        raise WebhookError("slack post failed: unexpected response")
+    if not result.get("ok"):
+        raise WebhookError("slack post failed: %s" % result.get("error", "unknown"))
+    return result
+
+
+def _post_matrix(capture, section, config, text):
+    """Matrix: upload to the media repo, then send an m.image event."""
+    homeserver = config.get(section, "url")
+    token = config.get(section, "token")
+    room = config.get(section, "room")
+    if not homeserver or not token or not room:
+        raise WebhookError("set url, token, and room under [%s] in the config" % section)
+    homeserver = homeserver.rstrip("/")
+    headers = {"Authorization": "Bearer " + token}
+    upload = _request(
+        urllib.request.Request(
+            "%s/_matrix/media/v3/upload?filename=%s" % (homeserver, _filename()),
+            data=capture.to_png_bytes(),
+            headers=dict(headers, **{"Content-Type": "image/png"}),
+        ),
+        "matrix upload failed",
+    )
+    try:
+        content_uri = json.loads(upload)["content_uri"]
+    except (ValueError, KeyError):
+        raise WebhookError("matrix upload failed: unexpected response")
+    event = {"msgtype": "m.image", "body": _filename(), "url": content_uri}
+    _request(
+        urllib.request.Request(
+            "%s/_matrix/client/v3/rooms/%s/send/m.room.message/%s"
+            % (homeserver, urllib.parse.quote(room), uuid.uuid4().hex),
+            data=json.dumps(event).encode(),
+            headers=dict(headers, **{"Content-Type": "application/json"}),
+            method="PUT",
+        ),
+        "matrix send failed",
+    )
+    if text:
+        _request(
+            urllib.request.Request(
+                "%s/_matrix/client/v3/rooms/%s/send/m.room.message/%s"
+                % (homeserver, urllib.parse.quote(room), uuid.uuid4().hex),
+                data=json.dumps({"msgtype": "m.text", "body": text}).encode(),
+                headers=dict(headers, **{"Content-Type": "application/json"}),
+                method="PUT",
+            ),
+            "matrix send failed",
+        )
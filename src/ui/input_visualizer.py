@@ -0,0 +1,90 @@
+import time
+
+from PyQt5.QtCore import Qt, QTimer
+from PyQt5.QtGui import QColor, QFont, QPainter, QPen
+from PyQt5.QtWidgets import QWidget
+
+RIPPLE_LIFETIME = 0.6  # seconds a click ripple stays visible
+BADGE_LIFETIME = 1.5   # seconds a pressed-key badge stays visible
+
+
+class InputVisualizer(QWidget):
+    """Click ripples and pressed-key badges rendered over the whole screen.
+
+    The overlay is click-through and always on top, so it ends up inside the
+    screen recording without interfering with the demo being recorded.
+    Global events come from pynput listeners.
+    """
+
+    def __init__(self):
+        super().__init__()
+        self.clicks = []  # (x, y, timestamp)
+        self.keys = []    # (label, timestamp)
+        self.listeners = []
+        self.setWindowFlags(
+            Qt.FramelessWindowHint | Qt.WindowStaysOnTopHint | Qt.Tool
+        )
+        self.setAttribute(Qt.WA_TranslucentBackground)
+        self.setAttribute(Qt.WA_TransparentForMouseEvents)
+        self.timer = QTimer(self)
+        self.timer.timeout.connect(self._expire)
+        self.timer.start(50)
+
+    def start(self):
+        from pynput import keyboard, mouse
+
+        def on_click(x, y, button, pressed):
+            if pressed:
+                self.clicks.append((x, y, time.time()))
+
+        def on_press(key):
+            self.keys.append((_key_label(key), time.time()))
+            # Keep only the most recent few so long bursts stay readable.
+            del self.keys[:-4]
+
+        self.listeners = [
+            mouse.Listener(on_click=on_click),
+            keyboard.Listener(on_press=on_press),
+        ]
+        for listener in self.listeners:
+            listener.start()
+        self.showFullScreen()
+
+    def stop(self):
+        for listener in self.listeners:
+            listener.stop()
+        self.close()
+
+    def _expire(self):
+        now = time.time()
+        self.clicks = [c for c in self.clicks if now - c[2] < RIPPLE_LIFETIME]
+        self.keys = [k for k in self.keys if now - k[1] < BADGE_LIFETIME]
+        self.update()
+
+    def paintEvent(self, event):
+        painter = QPainter(self)
+        painter.setRenderHint(QPainter.Antialiasing)
+        now = time.time()
+        for x, y, stamp in self.clicks:
+            progress = (now - stamp) / RIPPLE_LIFETIME
+            radius = int(10 + 30 * progress)
+            alpha = int(200 * (1 - progress))
+            painter.setPen(QPen(QColor(255, 200, 0, alpha), 3))
+            painter.drawEllipse(x - radius, y - radius, radius * 2, radius * 2)
+        if self.keys:
+            badge = "  ".join(label for label, _ in self.keys)
+            painter.setFont(QFont("Sans", 18, QFont.Bold))
+            painter.setPen(QColor(255, 255, 255, 230))
+            rect = self.rect()
+            painter.fillRect(
+                rect.x() + 20, rect.bottom() - 70, 12 * len(badge) + 20, 44,
+                QColor(0, 0, 0, 160),
+            )
+            painter.drawText(rect.x() + 30, rect.bottom() - 40, badge)
+
+
+def _key_label(key):
+    name = getattr(key, "char", None)
+    if name:
+        return name
+    return str(key).replace("Key.", "").replace("_", " ").title()
@@ -0,0 +1,8 @@
+from PyQt5.QtWidgets import QMainWindow
+
+
+class MainWindow(QMainWindow):
+    def __init__(self):
+        super().__init__()
+        self.setWindowTitle("OpenShot X - Screen Capture Tool")
+        self.setGeometry(100, 100, 600, 400)  # Set the dimensions of the window (x, y, width, height)
@@ -1 +1,497 @@
+from PyQt5.QtCore import Qt, QEvent, QEventLoop, QObject, QRect, QPoint
+from PyQt5.QtGui import QPainter, QColor, QPen
+from PyQt5.QtWidgets import QWidget
 
+
+class _CloseWatcher(QObject):
+    """Quits an event loop when the watched widget receives a Close event."""
+
+    def __init__(self, loop):
+        super().__init__()
+        self._loop = loop
+
+    def eventFilter(self, _obj, event):
+        if event.type() == QEvent.Close:
+            self._loop.quit()
+        return False
+
+
+def _wait_until_closed(widget):
+    """Block in a real event loop until the widget is closed.
+
+    A `while isVisible(): processEvents()` spin pegs a CPU core for as long
+    as the widget is up — the recording indicator would compete with the
+    encoder for an entire recording. Watching for Close (not Hide) also
+    lets widgets hide themselves temporarily, as the live-OCR overlay does
+    around its grabs.
+    """
+    loop = QEventLoop()
+    watcher = _CloseWatcher(loop)
+    widget.installEventFilter(watcher)
+    if widget.isVisible():
+        loop.exec_()
+
+
+class SelectionOverlay(QWidget):
+    """Fullscreen overlay for dragging out a capture region.
+
+    Holding Shift while dragging snaps the selection to the configured grid
+    (size taken from [overlay] grid in the config, default 8px), so regions
+    line up with design grids and repeated shots come out the same size.
+    Holding Ctrl snaps to strong content edges detected in the frame, so
+    selections latch onto window borders and panels.
+    """
+
+    def __init__(
+        self, grid_size=8, edge_map=None, edge_source=None, theme="default", background=None
+    ):
+        super().__init__()
+        self.grid_size = grid_size
+        self.edge_map = edge_map  # (xs, ys) from utils.edges.detect_edges
+        # Frame (PIL image) to detect edges in, on the first Ctrl press;
+        # most selections never snap, so the work is deferred until asked.
+        self.edge_source = edge_source
+        # "high-contrast" drops the alpha dimming and uses thick solid borders,
+        # for low-vision users and compositors that mis-render transparency.
+        self.theme = theme
+        # A frozen frame (PNG bytes) painted under the overlay; with it in
+        # place the user selects on a still image instead of the live screen.
+        self.background = None
+        if background is not None:
+            from PyQt5.QtGui import QPixmap
+
+            pixmap = QPixmap()
+            pixmap.loadFromData(background)
+            self.background = pixmap
+        self.origin = None
+        self.current = None
+        self.result = None  # (x, y, w, h) once the user releases the mouse
+        self.setWindowFlags(
+            Qt.FramelessWindowHint | Qt.WindowStaysOnTopHint | Qt.Tool
+        )
+        self.setAttribute(Qt.WA_TranslucentBackground)
+        self.setCursor(Qt.CrossCursor)
+
+    def _snap(self, point):
+        """Snap a point to the grid (Shift) or detected content edges (Ctrl)."""
+        from PyQt5.QtWidgets import QApplication
+
+        modifiers = QApplication.keyboardModifiers()
+        if modifiers & Qt.ControlModifier and self.edge_map is None and self.edge_source is not None:
+            from utils.edges import detect_edges
+
+            self.edge_map = detect_edges(self.edge_source)
+            self.edge_source = None
+        if modifiers & Qt.ControlModifier and self.edge_map is not None:
+            from utils.edges import snap_to_edges
+
+            xs, ys = self.edge_map
+            return QPoint(
+                snap_to_edges(point.x(), xs),
+                snap_to_edges(point.y(), ys),
+            )
+        if modifiers & Qt.ShiftModifier:
+            grid = self.grid_size
+            return QPoint(
+                round(point.x() / grid) * grid,
+                round(point.y() / grid) * grid,
+            )
+        return point
+
+    def selection_rect(self):
+        if self.origin is None or self.current is None:
+            return QRect()
+        return QRect(self.origin, self.current).normalized()
+
+    def mousePressEvent(self, event):
+        self.origin = self._snap(event.pos())
+        self.current = self.origin
+        self.update()
+
+    def mouseMoveEvent(self, event):
+        if self.origin is not None:
+            self.current = self._snap(event.pos())
+            self.update()
+
+    def mouseReleaseEvent(self, event):
+        rect = self.selection_rect()
+        if rect.width() > 0 and rect.height() > 0:
+            self.result = (rect.x(), rect.y(), rect.width(), rect.height())
+        self.close()
+
+    # Arrow-key step sizes for keyboard-only selection.
+    KEY_STEP = 16
+    KEY_STEP_FINE = 2
+
+    def keyPressEvent(self, event):
+        key = event.key()
+        if key == Qt.Key_Escape:
+            self.close()
+            return
+        arrows = {
+            Qt.Key_Left: (-1, 0),
+            Qt.Key_Right: (1, 0),
+            Qt.Key_Up: (0, -1),
+            Qt.Key_Down: (0, 1),
+        }
+        if key in arrows:
+            self._keyboard_adjust(arrows[key], event.modifiers())
+        elif key in (Qt.Key_Return, Qt.Key_Enter) and self.origin is not None:
+            rect = self.selection_rect()
+            if rect.width() > 0 and rect.height() > 0:
+                self.result = (rect.x(), rect.y(), rect.width(), rect.height())
+                self._announce("selection confirmed")
+            self.close()
+
+    def _keyboard_adjust(self, direction, modifiers):
+        """Move (or resize, with Shift) the selection entirely from the keyboard.
+
+        The first arrow press seeds a selection at the screen centre so the
+        whole flow works without any mouse drag.
+        """
+        if self.origin is None:
+            centre = self.rect().center()
+            self.origin = QPoint(centre.x() - 100, centre.y() - 100)
+            self.current = QPoint(centre.x() + 100, centre.y() + 100)
+            self._announce("selection started at screen centre")
+        dx, dy = direction
+        step = self.KEY_STEP_FINE if modifiers & Qt.AltModifier else self.KEY_STEP
+        delta = QPoint(dx * step, dy * step)
+        if modifiers & Qt.ShiftModifier:
+            self.current += delta  # resize by moving the floating corner
+        else:
+            self.origin += delta
+            self.current += delta
+        rect = self.selection_rect()
+        self._announce(
+            "%d by %d at %d, %d" % (rect.width(), rect.height(), rect.x(), rect.y())
+        )
+        self.update()
+
+    def _announce(self, message):
+        """Best-effort screen-reader announcement through speech-dispatcher."""
+        import subprocess
+
+        try:
+            subprocess.Popen(
+                ["spd-say", "--cancel", "--", message],
+                stdout=subprocess.DEVNULL,
+                stderr=subprocess.DEVNULL,
+            )
+        except OSError:
+            pass  # no speech-dispatcher; selection still works silently
+
+    def paintEvent(self, event):
+        painter = QPainter(self)
+        rect = self.selection_rect()
+        if self.background is not None:
+            painter.drawPixmap(self.rect(), self.background)
+        if self.theme == "high-contrast":
+            if not rect.isNull():
+                # Double border (black inside yellow) stays visible on any
+                # background without relying on compositor transparency.
+                painter.setPen(QPen(QColor(255, 255, 0), 6))
+                painter.drawRect(rect)
+                painter.setPen(QPen(QColor(0, 0, 0), 2))
+                painter.drawRect(rect.adjusted(2, 2, -2, -2))
+            return
+        # Dim everything outside the selection.
+        painter.fillRect(self.rect(), QColor(0, 0, 0, 120))
+        if not rect.isNull():
+            if self.background is not None:
+                # Re-paint the frozen pixels instead of punching a hole to
+                # the (still animating) live screen below.
+                painter.drawPixmap(rect, self.background, rect)
+            else:
+                painter.setCompositionMode(QPainter.CompositionMode_Clear)
+                painter.fillRect(rect, Qt.transparent)
+                painter.setCompositionMode(QPainter.CompositionMode_SourceOver)
+            painter.setPen(QPen(QColor(64, 156, 255), 2))
+            painter.drawRect(rect)
+
+
+class LiveOcrOverlay(QWidget):
+    """Experimental hover-to-OCR mode: point, press Space, get text.
+
+    A translucent overlay follows the cursor with a paragraph-sized frame;
+    pressing Space (or Enter) OCRs just that region of the live screen and
+    copies the result, skipping the select-drag dance entirely. Escape
+    leaves the mode.
+    """
+
+    # Roughly one paragraph of a maximised window at 1080p.
+    REGION_W = 700
+    REGION_H = 220
+
+    def __init__(self, display=None):
+        super().__init__()
+        self.display = display
+        self.last_text = "hover and press Space to read"
+        self.setWindowFlags(
+            Qt.FramelessWindowHint | Qt.WindowStaysOnTopHint | Qt.Tool
+        )
+        self.setAttribute(Qt.WA_TranslucentBackground)
+        self.setCursor(Qt.CrossCursor)
+        self.setMouseTracking(True)
+
+    def _hover_region(self):
+        """Paragraph-sized box centred on the cursor, clamped to the screen."""
+        from PyQt5.QtGui import QCursor
+
+        pos = self.mapFromGlobal(QCursor.pos())
+        x = max(0, min(pos.x() - self.REGION_W // 2, self.width() - self.REGION_W))
+        y = max(0, min(pos.y() - self.REGION_H // 2, self.height() - self.REGION_H))
+        return (x, y, self.REGION_W, self.REGION_H)
+
+    def mouseMoveEvent(self, event):
+        self.update()
+
+    def keyPressEvent(self, event):
+        key = event.key()
+        if key == Qt.Key_Escape:
+            self.close()
+        elif key in (Qt.Key_Space, Qt.Key_Return, Qt.Key_Enter):
+            self._recognize()
+
+    def _recognize(self):
+        from PyQt5.QtWidgets import QApplication
+
+        from capture import screenshot
+        from capture.ocr import extract_text, record_history
+        from utils.clipboard import copy_text
+
+        region = self._hover_region()
+        # Drop out of the way so the overlay isn't in its own capture.
+        self.hide()
+        QApplication.processEvents()
+        try:
+            data = screenshot.capture_region(region, display=self.display)
+        finally:
+            self.show()
+        text = extract_text(data)
+        if text:
+            copy_text(text)
+            record_history(text)
+        self.last_text = text or "(no text found)"
+        self.update()
+
+    def paintEvent(self, event):
+        painter = QPainter(self)
+        x, y, w, h = self._hover_region()
+        painter.setPen(QPen(QColor(64, 156, 255), 2))
+        painter.drawRect(QRect(x, y, w, h))
+        preview = " ".join(self.last_text.split())
+        if len(preview) > 90:
+            preview = preview[:89] + "…"
+        painter.fillRect(0, self.height() - 32, self.width(), 32, QColor(0, 0, 0, 180))
+        painter.setPen(QColor(255, 255, 255))
+        painter.drawText(
+            QRect(8, self.height() - 32, self.width() - 16, 32),
+            Qt.AlignVCenter,
+            preview,
+        )
+
+
+def run_live_ocr(display=None):
+    """Run the hover-OCR overlay until the user presses Escape."""
+    from PyQt5.QtWidgets import QApplication
+
+    QApplication.instance() or QApplication([])
+    overlay = LiveOcrOverlay(display=display)
+    overlay.showFullScreen()
+    _wait_until_closed(overlay)
+
+
+class WindowPicker(QWidget):
+    """Horizontal strip of live window thumbnails for `capture window`.
+
+    Thumbnails are cropped out of a fullscreen grab, so the picker shows what
+    each window currently looks like rather than titles alone.
+    """
+
+    THUMB_HEIGHT = 160
+
+    def __init__(self, windows, frame, multi=False):
+        super().__init__()
+        import io
+
+        from PyQt5.QtCore import QSize
+        from PyQt5.QtGui import QIcon, QPixmap
+        from PyQt5.QtWidgets import QHBoxLayout, QPushButton, QToolButton
+
+        self.setWindowFlags(Qt.FramelessWindowHint | Qt.WindowStaysOnTopHint)
+        self.setWindowTitle("Pick a window")
+        self.multi = multi
+        self.result = None
+        self.selected = []
+        layout = QHBoxLayout(self)
+        for window in windows:
+            crop = frame.crop(
+                (
+                    max(window.x, 0),
+                    max(window.y, 0),
+                    max(window.x, 0) + window.width,
+                    max(window.y, 0) + window.height,
+                )
+            )
+            buf = io.BytesIO()
+            crop.save(buf, format="PNG")
+            pixmap = QPixmap()
+            pixmap.loadFromData(buf.getvalue())
+            pixmap = pixmap.scaledToHeight(self.THUMB_HEIGHT, Qt.SmoothTransformation)
+            button = QToolButton()
+            button.setIcon(QIcon(pixmap))
+            button.setIconSize(QSize(pixmap.width(), pixmap.height()))
+            button.setText(window.title[:40])
+            button.setToolButtonStyle(Qt.ToolButtonTextUnderIcon)
+            if multi:
+                button.setCheckable(True)
+                button.toggled.connect(
+                    lambda checked, w=window: self._toggle(w, checked)
+                )
+            else:
+                button.clicked.connect(lambda _checked, w=window: self._pick(w))
+            layout.addWidget(button)
+        if multi:
+            done = QPushButton("Capture selected")
+            done.clicked.connect(self._done)
+            layout.addWidget(done)
+
+    def _pick(self, window):
+        self.result = window
+        self.close()
+
+    def _toggle(self, window, checked):
+        if checked:
+            self.selected.append(window)
+        elif window in self.selected:
+            self.selected.remove(window)
+
+    def _done(self):
+        if self.selected:
+            self.result = list(self.selected)
+        self.close()
+
+    def keyPressEvent(self, event):
+        if event.key() == Qt.Key_Escape:
+            self.close()
+
+
+def pick_window(windows, frame, multi=False):
+    """Show the thumbnail picker and return the chosen WindowInfo or None.
+
+    With multi=True, returns a list of WindowInfo selected via checkable
+    thumbnails and the "Capture selected" button.
+    """
+    from PyQt5.QtWidgets import QApplication
+
+    QApplication.instance() or QApplication([])
+    picker = WindowPicker(windows, frame, multi=multi)
+    picker.show()
+    _wait_until_closed(picker)
+    return picker.result
+
+
+class RecordingIndicator(QWidget):
+    """Small always-on-top pill showing elapsed recording time and a stop button."""
+
+    def __init__(self, on_stop, on_pause=None):
+        super().__init__()
+        from PyQt5.QtCore import QTimer
+        from PyQt5.QtWidgets import QHBoxLayout, QLabel, QPushButton
+
+        self.on_stop = on_stop
+        self.on_pause = on_pause
+        self.seconds = 0
+        self.paused = False
+        self.setWindowFlags(Qt.FramelessWindowHint | Qt.WindowStaysOnTopHint | Qt.Tool)
+        layout = QHBoxLayout(self)
+        self.label = QLabel("● 0:00")
+        self.label.setStyleSheet("color: #d33; font-weight: bold;")
+        layout.addWidget(self.label)
+        if on_pause is not None:
+            self.pause_button = QPushButton("Pause")
+            self.pause_button.clicked.connect(self._pause_clicked)
+            layout.addWidget(self.pause_button)
+        stop = QPushButton("Stop")
+        stop.clicked.connect(self._stop_clicked)
+        layout.addWidget(stop)
+        self.timer = QTimer(self)
+        self.timer.timeout.connect(self._tick)
+        self.timer.start(1000)
+
+    def _tick(self):
+        if self.paused:
+            return  # elapsed time only counts recorded footage
+        self.seconds += 1
+        self.label.setText("● %d:%02d" % divmod(self.seconds, 60))
+
+    def _pause_clicked(self):
+        self.paused = not self.paused
+        self.pause_button.setText("Resume" if self.paused else "Pause")
+        self.on_pause()
+
+    def _stop_clicked(self):
+        self.timer.stop()
+        self.on_stop()
+        self.close()
+
+
+def show_recording_indicator(on_stop, on_pause=None):
+    """Run the indicator event loop until the recording is stopped.
+
+    Blocks in place of Recorder.wait(); the stop button (or closing the
+    indicator) invokes on_stop, so there is always a visible way to end a
+    recording besides killing the process.
+    """
+    from PyQt5.QtWidgets import QApplication
+
+    QApplication.instance() or QApplication([])
+    indicator = RecordingIndicator(on_stop, on_pause=on_pause)
+    indicator.show()
+    _wait_until_closed(indicator)
+
+
+def choose_save_path(suggested_name, directory=None):
+    """Ask for a save destination with the native file dialog.
+
+    Inside a Flatpak/Snap, Qt routes this through the FileChooser portal, so
+    the sandboxed build can write outside its sandbox and the portal's
+    suggested-filename handling is honoured.
+
+    Returns the chosen path or None if the dialog was cancelled.
+    """
+    import os
+
+    from PyQt5.QtWidgets import QApplication, QFileDialog
+
+    QApplication.instance() or QApplication([])
+    start = os.path.join(directory or os.path.expanduser("~"), suggested_name)
+    path, _ = QFileDialog.getSaveFileName(
+        None, "Save capture", start, "Images (*.png *.jpg *.webp)"
+    )
+    return path or None
+
+
+def select_region_interactively(
+    grid_size=8, edge_map=None, edge_source=None, theme="default", background=None
+):
+    """Show the selection overlay and block until a region is picked.
+
+    background, when given, is PNG bytes of a frozen frame to select on;
+    edge_source is a PIL frame to detect Ctrl-snap edges in lazily.
+    Returns (x, y, w, h) or None if the user pressed Escape.
+    """
+    from PyQt5.QtWidgets import QApplication
+
+    QApplication.instance() or QApplication([])
+    overlay = SelectionOverlay(
+        grid_size=grid_size,
+        edge_map=edge_map,
+        edge_source=edge_source,
+        theme=theme,
+        background=background,
+    )
+    overlay.showFullScreen()
+    _wait_until_closed(overlay)
+    return overlay.result
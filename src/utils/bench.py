@@ -0,0 +1,62 @@
+import io
+import time
+
+from PIL import Image, ImageDraw
+
+
+def _synthetic_image(width=1920, height=1080):
+    """A gradient-plus-text frame that exercises both encoders and OCR."""
+    image = Image.new("RGBA", (width, height))
+    for y in range(height):
+        for x in range(0, width, 4):
+            value = (x * 255 // width, y * 255 // height, 128, 255)
+            for dx in range(4):
+                image.putpixel((min(x + dx, width - 1), y), value)
+    draw = ImageDraw.Draw(image)
+    for i in range(10):
+        draw.text((40, 40 + i * 90), "The quick brown fox jumps over the lazy dog", fill="white")
+    return image
+
+
+def _timed(fn):
+    start = time.perf_counter()
+    fn()
+    return time.perf_counter() - start
+
+
+def run_benchmarks():
+    """Measure capture, conversion, encode, and OCR timings.
+
+    Returns a list of (name, seconds-or-None) so regressions across releases
+    and machines can be compared; None means the step was unavailable here.
+    """
+    results = []
+    image = _synthetic_image()
+
+    from capture import screenshot
+
+    try:
+        results.append(("capture fullscreen", _timed(screenshot.capture_fullscreen)))
+    except screenshot.CaptureError:
+        results.append(("capture fullscreen", None))
+
+    results.append(("convert to RGB", _timed(lambda: image.convert("RGB"))))
+    for fmt in ("PNG", "JPEG", "WEBP"):
+        frame = image.convert("RGB")
+        results.append(
+            ("encode %s" % fmt.lower(), _timed(lambda: frame.save(io.BytesIO(), format=fmt)))
+        )
+
+    # Import separately from running: if the import itself fails (no
+    # pytesseract), OcrError never gets bound and can't appear in an
+    # except clause.
+    try:
+        from capture.ocr import extract_text, OcrError
+    except ImportError:
+        results.append(("ocr", None))
+        return results
+    try:
+        results.append(("ocr", _timed(lambda: extract_text(image))))
+    except OcrError:
+        results.append(("ocr", None))
+    return results
@@ -0,0 +1,33 @@
+import subprocess
+
+from capture.screenshot import CaptureError, is_wayland
+
+
+class ClipboardError(CaptureError):
+    pass
+
+
+def _pipe_to(args, data):
+    try:
+        subprocess.run(args, input=data, check=True)
+    except OSError as exc:
+        raise ClipboardError("%s is not installed" % args[0]) from exc
+    except subprocess.CalledProcessError as exc:
+        raise ClipboardError("%s failed" % args[0]) from exc
+
+
+def copy_text(text):
+    """Put text on the clipboard via wl-copy (Wayland) or xclip (X11)."""
+    if is_wayland():
+        _pipe_to(["wl-copy"], text.encode())
+    else:
+        _pipe_to(["xclip", "-selection", "clipboard"], text.encode())
+
+
+def copy_image(capture):
+    """Put a capture on the clipboard as image/png."""
+    data = capture.to_png_bytes()
+    if is_wayland():
+        _pipe_to(["wl-copy", "--type", "image/png"], data)
+    else:
+        _pipe_to(["xclip", "-selection", "clipboard", "-t", "image/png"], data)
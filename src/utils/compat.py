@@ -0,0 +1,88 @@
+"""Command-line compatibility shims for other screenshot tools.
+
+Existing keybindings and scripts written for flameshot keep working when
+the binary is symlinked/aliased to `flameshot`, or explicitly via
+`openshotx flameshot-compat ...`.
+"""
+
+
+def from_flameshot(argv):
+    """Map flameshot-style arguments onto our own CLI argument list."""
+    if not argv:
+        return ["capture", "area"]
+
+    mode, rest = argv[0], argv[1:]
+    if mode == "gui":
+        out = ["capture", "area"]
+    elif mode == "full":
+        out = ["capture", "screen"]
+    elif mode == "screen":
+        out = ["capture", "screen"]
+    else:
+        # Unknown mode: pass through untouched and let argparse complain.
+        return argv
+
+    clipboard = False
+    i = 0
+    while i < len(rest):
+        arg = rest[i]
+        if arg in ("-p", "--path"):
+            out += ["--output", rest[i + 1]]
+            i += 2
+        elif arg in ("-c", "--clipboard"):
+            clipboard = True
+            i += 1
+        elif arg in ("-d", "--delay"):
+            # flameshot takes milliseconds; we take seconds.
+            out += ["--delay", str(int(rest[i + 1]) / 1000)]
+            i += 2
+        elif arg in ("-n", "--number"):
+            i += 2  # monitor index; full-desktop capture covers it for now
+        else:
+            i += 1  # ignore flags we have no equivalent for
+    if clipboard:
+        out += ["--to", "clipboard"]
+    return out
+
+
+def from_maim(argv):
+    """Map the common maim/scrot flags onto our own CLI argument list.
+
+    Covers `-g GEOMETRY`, `-d SEC`, quality `-m`/`-q`, and a trailing output
+    file; selection flags we have no equivalent for are skipped so dotfiles
+    keep working.
+    """
+    geometry = None
+    output = None
+    extra = []
+    i = 0
+    while i < len(argv):
+        arg = argv[i]
+        if arg == "-g":
+            geometry = argv[i + 1]
+            i += 2
+        elif arg == "-d":
+            extra += ["--delay", argv[i + 1]]
+            i += 2
+        elif arg in ("-m", "-q"):
+            extra += ["--quality", argv[i + 1]]
+            i += 2
+        elif arg in ("-u", "-s", "-o"):
+            i += 1  # cursor/select/overwrite flags without a direct mapping
+        elif arg == "-i":
+            i += 2  # window id capture is not supported yet
+        elif not arg.startswith("-"):
+            output = arg
+            i += 1
+        else:
+            i += 1
+    # Assemble once at the end so flag order doesn't matter: `-d 2 -g …`
+    # must keep the delay just like `-g … -d 2` does.
+    if geometry:
+        out = ["capture", "area", "--geometry", geometry]
+    else:
+        out = ["capture", "screen"]
+    out += extra
+    if output:
+        out += ["--output", output]
+    return out
@@ -0,0 +1,94 @@
+import glob
+import os
+import shutil
+import subprocess
+
+
+def _tool(name):
+    return shutil.which(name) is not None
+
+
+def _tesseract_langs():
+    try:
+        out = subprocess.run(
+            ["tesseract", "--list-langs"], capture_output=True, text=True, check=True
+        ).stdout
+    except (OSError, subprocess.CalledProcessError):
+        return []
+    return [line.strip() for line in out.splitlines()[1:] if line.strip()]
+
+
+def _ffmpeg_gpu_encoders():
+    try:
+        out = subprocess.run(
+            ["ffmpeg", "-hide_banner", "-encoders"], capture_output=True, text=True, check=True
+        ).stdout
+    except (OSError, subprocess.CalledProcessError):
+        return []
+    return [name for name in ("h264_vaapi", "h264_nvenc") if name in out]
+
+
+def run_checks():
+    """Probe the environment and return a list of check dicts.
+
+    Each entry has name, ok, detail, and hint keys; `openshotx doctor`
+    renders them as pass/fail lines or as JSON.
+    """
+    wayland = bool(os.environ.get("WAYLAND_DISPLAY"))
+    x11 = bool(os.environ.get("DISPLAY"))
+    grabber = "grim" if wayland else "maim"
+    langs = _tesseract_langs()
+    encoders = _ffmpeg_gpu_encoders()
+    portal = _tool("xdg-desktop-portal") or bool(
+        glob.glob("/usr/lib*/xdg-desktop-portal") + glob.glob("/usr/libexec/xdg-desktop-portal")
+    )
+    clipboard_tool = "wl-copy" if wayland else "xclip"
+
+    from utils.sandbox import sandbox_kind
+
+    kind = sandbox_kind()
+
+    return [
+        {
+            "name": "sandbox",
+            "ok": True,  # informational: sandboxing changes paths, not correctness
+            "detail": kind or "none",
+            "hint": "",
+        },
+        {
+            "name": "display server",
+            "ok": wayland or x11,
+            "detail": "wayland" if wayland else ("x11" if x11 else "none"),
+            "hint": "no WAYLAND_DISPLAY or DISPLAY set; run inside a graphical session",
+        },
+        {
+            "name": "screen grabber (%s)" % grabber,
+            "ok": _tool(grabber),
+            "detail": shutil.which(grabber) or "not found",
+            "hint": "install %s with your package manager" % grabber,
+        },
+        {
+            "name": "desktop portal",
+            "ok": portal,
+            "detail": "found" if portal else "not found",
+            "hint": "install xdg-desktop-portal plus a backend for your desktop",
+        },
+        {
+            "name": "clipboard (%s)" % clipboard_tool,
+            "ok": _tool(clipboard_tool),
+            "detail": shutil.which(clipboard_tool) or "not found",
+            "hint": "install %s for clipboard sinks" % clipboard_tool,
+        },
+        {
+            "name": "tesseract",
+            "ok": bool(langs),
+            "detail": "languages: %s" % ", ".join(langs) if langs else "not found",
+            "hint": "install tesseract and at least one language pack for OCR",
+        },
+        {
+            "name": "ffmpeg",
+            "ok": _tool("ffmpeg"),
+            "detail": "gpu encoders: %s" % (", ".join(encoders) or "none"),
+            "hint": "install ffmpeg for recording and conversion features",
+        },
+    ]
@@ -0,0 +1,61 @@
+from PIL import Image
+
+
+def composite_layout(crops):
+    """Composite window crops preserving their on-screen layout.
+
+    crops is a list of (image, (x, y, w, h)); the canvas is the bounding box
+    of all regions, so relative positions survive into the output image.
+    """
+    min_x = min(region[0] for _, region in crops)
+    min_y = min(region[1] for _, region in crops)
+    max_x = max(region[0] + region[2] for _, region in crops)
+    max_y = max(region[1] + region[3] for _, region in crops)
+    canvas = Image.new("RGBA", (max_x - min_x, max_y - min_y), (0, 0, 0, 0))
+    for image, (x, y, _w, _h) in crops:
+        canvas.paste(image, (x - min_x, y - min_y))
+    return canvas
+
+
+def contact_sheet(entries, columns=4, thumb_size=(320, 200), gap=12):
+    """Grid of labelled thumbnails for reviewing a stretch of captures.
+
+    entries is a list of (image, label); labels (typically timestamps) are
+    drawn under each thumbnail so the sheet reads as a timeline.
+    """
+    from PIL import ImageDraw
+
+    label_height = 16
+    cell_w = thumb_size[0]
+    cell_h = thumb_size[1] + label_height
+    rows = (len(entries) + columns - 1) // columns
+    canvas = Image.new(
+        "RGB",
+        (columns * cell_w + gap * (columns + 1), rows * cell_h + gap * (rows + 1)),
+        (24, 24, 24),
+    )
+    draw = ImageDraw.Draw(canvas)
+    for index, (image, label) in enumerate(entries):
+        thumb = image.convert("RGB")
+        thumb.thumbnail(thumb_size)
+        x = gap + (index % columns) * (cell_w + gap)
+        y = gap + (index // columns) * (cell_h + gap)
+        canvas.paste(
+            thumb,
+            (x + (cell_w - thumb.width) // 2, y + (thumb_size[1] - thumb.height) // 2),
+        )
+        draw.text((x, y + thumb_size[1] + 2), label, fill=(200, 200, 200))
+    return canvas
+
+
+def composite_row(crops, gap=16):
+    """Composite window crops side by side, for before/after style shots."""
+    images = [image for image, _region in crops]
+    width = sum(image.width for image in images) + gap * (len(images) - 1)
+    height = max(image.height for image in images)
+    canvas = Image.new("RGBA", (width, height), (0, 0, 0, 0))
+    x = 0
+    for image in images:
+        canvas.paste(image, (x, (height - image.height) // 2))
+        x += image.width + gap
+    return canvas
@@ -0,0 +1,67 @@
+import re
+from dataclasses import dataclass
+
+
+@dataclass
+class Region:
+    x: int
+    y: int
+    width: int
+    height: int
+
+    def as_tuple(self):
+        return (self.x, self.y, self.width, self.height)
+
+
+# WxH+X+Y where each component may be a plain pixel value or a percentage;
+# offsets may be negative (maim writes those as '+-50').
+_GEOMETRY_RE = re.compile(
+    r"^(\d+%?)x(\d+%?)\+(-?\d+%?)\+(-?\d+%?)$"
+)
+
+
+def _resolve_component(value, reference):
+    """Turn '25%' into pixels against a reference dimension; pass pixels through."""
+    if value.endswith("%"):
+        return int(reference * int(value[:-1]) / 100)
+    return int(value)
+
+
+def _resolve_offset(value, origin, reference):
+    """Resolve an X/Y offset component to a root coordinate.
+
+    Percentages are relative to the target monitor and shifted to its
+    origin so they land on the right output. Plain pixel offsets are
+    root-absolute, matching maim and scrot — offsetting those by the
+    monitor position would capture the wrong region whenever the primary
+    monitor doesn't sit at the origin of the virtual screen.
+    """
+    if value.endswith("%"):
+        return origin + int(reference * int(value[:-1]) / 100)
+    return int(value)
+
+
+def parse_geometry(spec, monitor):
+    """Parse a geometry spec like '50%x50%+25%+25%' or '800x600+100+100'.
+
+    Percentage components are resolved against the target monitor; pixel
+    offsets are taken as root-absolute coordinates, maim-style.
+    """
+    match = _GEOMETRY_RE.match(spec.strip())
+    if match is None:
+        raise ValueError("invalid geometry %r, expected WxH+X+Y" % spec)
+    w, h, x, y = match.groups()
+    return Region(
+        x=_resolve_offset(x, monitor.x, monitor.width),
+        y=_resolve_offset(y, monitor.y, monitor.height),
+        width=_resolve_component(w, monitor.width),
+        height=_resolve_component(h, monitor.height),
+    )
+
+
+def resolve_region(spec, monitor, presets=None):
+    """Resolve a --geometry argument: either a geometry string or a named preset."""
+    presets = presets or {}
+    if spec in presets:
+        spec = presets[spec]
+    return parse_geometry(spec, monitor)
@@ -0,0 +1,20 @@
+import os
+
+from PIL import Image
+
+# VAAPI render node ffmpeg uses for hardware encoding; override for
+# multi-GPU boxes where the default node is the wrong card.
+RENDER_NODE = os.environ.get("OPENSHOTX_RENDER_NODE", "/dev/dri/renderD128")
+
+
+def scale_image(image, size):
+    """Downscale a still capture with a high-quality Lanczos resample.
+
+    There is deliberately no GPU path here: scale_vaapi only works on
+    video pixel formats like nv12, whose 4:2:0 chroma subsampling fringes
+    text, and a one-shot still would have to be PNG-encoded into ffmpeg
+    and decoded back out on the CPU anyway — the round-trip costs more
+    than the resize saves. VAAPI stays where it pays off: the recorder's
+    encode pipeline, which streams frames without ever leaving the GPU.
+    """
+    return image.resize(size, Image.LANCZOS)
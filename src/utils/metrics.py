@@ -0,0 +1,90 @@
+import time
+
+from utils import state
+
+# Histogram bucket upper bounds (seconds) for capture latency.
+BUCKETS = (0.1, 0.25, 0.5, 1.0, 2.5, 5.0)
+
+
+def record_capture(ok, seconds):
+    """Update the persisted capture counters.
+
+    Kiosk and monitoring deployments run captures on a schedule and need to
+    know when they quietly start failing; the counters back both
+    `openshotx status` and the Prometheus endpoint.
+    """
+    m = state.get("metrics") or {}
+    m["captures"] = m.get("captures", 0) + 1
+    if not ok:
+        m["failures"] = m.get("failures", 0) + 1
+    m["latency_sum"] = m.get("latency_sum", 0.0) + seconds
+    buckets = m.setdefault("buckets", {})
+    for le in BUCKETS:
+        if seconds <= le:
+            key = str(le)
+            buckets[key] = buckets.get(key, 0) + 1
+    m["last_capture"] = time.time()
+    state.set("metrics", m)
+
+
+def summary():
+    return state.get("metrics") or {}
+
+
+def render_prometheus():
+    """The counters in Prometheus text exposition format."""
+    m = summary()
+    captures = m.get("captures", 0)
+    lines = [
+        "# TYPE openshotx_captures_total counter",
+        "openshotx_captures_total %d" % captures,
+        "# TYPE openshotx_capture_failures_total counter",
+        "openshotx_capture_failures_total %d" % m.get("failures", 0),
+        "# TYPE openshotx_capture_duration_seconds histogram",
+    ]
+    cumulative = 0
+    for le in BUCKETS:
+        cumulative += m.get("buckets", {}).get(str(le), 0)
+        lines.append(
+            'openshotx_capture_duration_seconds_bucket{le="%s"} %d' % (le, cumulative)
+        )
+    lines.append(
+        'openshotx_capture_duration_seconds_bucket{le="+Inf"} %d' % captures
+    )
+    lines.append(
+        "openshotx_capture_duration_seconds_sum %f" % m.get("latency_sum", 0.0)
+    )
+    lines.append("openshotx_capture_duration_seconds_count %d" % captures)
+    if "last_capture" in m:
+        lines.append("# TYPE openshotx_last_capture_timestamp_seconds gauge")
+        lines.append(
+            "openshotx_last_capture_timestamp_seconds %f" % m["last_capture"]
+        )
+    return "\n".join(lines) + "\n"
+
+
+def serve(port):
+    """Serve /metrics and /healthz on localhost until interrupted."""
+    from http.server import BaseHTTPRequestHandler, HTTPServer
+
+    class Handler(BaseHTTPRequestHandler):
+        def do_GET(self):
+            if self.path == "/metrics":
+                body = render_prometheus().encode()
+            elif self.path == "/healthz":
+                body = b"ok\n"
+            else:
+                self.send_error(404)
+                return
+            self.send_response(200)
+            self.send_header("Content-Type", "text/plain; version=0.0.4")
+            self.send_header("Content-Length", str(len(body)))
+            self.end_headers()
+            self.wfile.write(body)
+
+        def log_message(self, *args):
+            pass  # scrapes every few seconds would flood stderr
+
+    server = HTTPServer(("127.0.0.1", port), Handler)
+    print("serving metrics on http://127.0.0.1:%d/metrics" % port)
+    server.serve_forever()
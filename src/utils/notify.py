@@ -0,0 +1,49 @@
+import subprocess
+
+
+def dnd_active():
+    """Whether the desktop's do-not-disturb mode is on (GNOME or KDE)."""
+    try:
+        out = subprocess.run(
+            ["gsettings", "get", "org.gnome.desktop.notifications", "show-banners"],
+            capture_output=True,
+            text=True,
+            check=True,
+        ).stdout.strip()
+        if out == "false":
+            return True
+    except (OSError, subprocess.CalledProcessError):
+        pass
+    try:
+        out = subprocess.run(
+            [
+                "kreadconfig5", "--file", "plasmanotifyrc",
+                "--group", "DoNotDisturb", "--key", "Enabled",
+            ],
+            capture_output=True,
+            text=True,
+            check=True,
+        ).stdout.strip()
+        if out == "true":
+            return True
+    except (OSError, subprocess.CalledProcessError):
+        pass
+    return False
+
+
+def notify(summary, body=""):
+    """Desktop notification that respects do-not-disturb.
+
+    Silently does nothing when DND is on or notify-send is missing, so callers
+    never have to care whether a banner actually appeared.
+    """
+    if dnd_active():
+        return
+    try:
+        subprocess.run(
+            ["notify-send", "--app-name=OpenShotX", summary, body],
+            check=True,
+            capture_output=True,
+        )
+    except (OSError, subprocess.CalledProcessError):
+        pass
@@ -0,0 +1,39 @@
+import subprocess
+
+from capture.screenshot import CaptureError
+
+
+def print_capture(capture, config):
+    """Send a capture to CUPS through lp, scaled to fit the page.
+
+    CUPS rasterizes the PNG itself, so no conversion is needed; fit-to-page
+    keeps a 4K grab from printing at one pixel per dot. An optional [print]
+    section picks a non-default queue and paper size:
+
+        [print]
+        printer = office-laser
+        media = A4
+    """
+    command = ["lp", "-t", "screenshot", "-o", "fit-to-page"]
+    printer = config.get("print", "printer")
+    if printer:
+        command += ["-d", printer]
+    media = config.get("print", "media")
+    if media:
+        command += ["-o", "media=" + media]
+    try:
+        result = subprocess.run(
+            command,
+            input=capture.to_png_bytes(),
+            capture_output=True,
+            check=True,
+        )
+    except OSError:
+        raise CaptureError("lp not found (is CUPS installed?)")
+    except subprocess.CalledProcessError as exc:
+        raise CaptureError(
+            "printing failed: %s" % exc.stderr.decode(errors="replace").strip()
+        )
+    # lp reports "request id is <queue>-<n> (1 file(s))"; pass that through
+    # so the job can be tracked with lpstat.
+    return result.stdout.decode(errors="replace").strip() or "sent to printer"
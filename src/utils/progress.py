@@ -0,0 +1,20 @@
+import sys
+
+# Long operations (save, upload, recording finalize) accept an optional
+# `progress` callable taking (fraction, message); fraction runs 0.0..1.0.
+# Keeping it a plain callable means the CLI can draw a bar, the GUI can
+# drive a spinner, and library callers can pass None and hear nothing.
+
+
+def stderr_progress(fraction, message):
+    """Single-line progress bar on stderr, silent when not a terminal."""
+    if not sys.stderr.isatty():
+        return
+    width = 24
+    filled = int(round(fraction * width))
+    sys.stderr.write(
+        "\r[%s%s] %s" % ("#" * filled, "-" * (width - filled), message.ljust(32))
+    )
+    if fraction >= 1.0:
+        sys.stderr.write("\n")
+    sys.stderr.flush()
@@ -0,0 +1,29 @@
+import os
+import subprocess
+
+
+def sandbox_kind():
+    """Return 'flatpak', 'snap', or None for an unconfined session."""
+    if os.environ.get("FLATPAK_ID") or os.path.exists("/.flatpak-info"):
+        return "flatpak"
+    if os.environ.get("SNAP"):
+        return "snap"
+    return None
+
+
+def pictures_dir():
+    """The user's real Pictures directory, resolved through xdg-user-dir.
+
+    Inside a sandbox this is the host path the portal grants access to, so
+    captures land somewhere the user can actually find instead of a hidden
+    per-app directory.
+    """
+    try:
+        out = subprocess.run(
+            ["xdg-user-dir", "PICTURES"], capture_output=True, text=True, check=True
+        ).stdout.strip()
+        if out and out != os.path.expanduser("~"):
+            return out
+    except (OSError, subprocess.CalledProcessError):
+        pass
+    return os.path.expanduser("~/Pictures")
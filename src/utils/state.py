@@ -0,0 +1,62 @@
+import json
+import os
+import shutil
+
+# Runtime state (last selection, portal tokens, upload delete-hashes, history)
+# lives under XDG_STATE_HOME so it is kept apart from both config and cache.
+STATE_DIR = os.path.join(
+    os.environ.get("XDG_STATE_HOME", os.path.expanduser("~/.local/state")), "openshotx"
+)
+
+
+def _path(key):
+    return os.path.join(STATE_DIR, key + ".json")
+
+
+def get(key, default=None):
+    """Read a state entry, returning default when missing or unreadable."""
+    try:
+        with open(_path(key)) as handle:
+            return json.load(handle)
+    except (OSError, ValueError):
+        return default
+
+
+def set(key, value):
+    """Persist a state entry as JSON."""
+    os.makedirs(STATE_DIR, exist_ok=True)
+    with open(_path(key), "w") as handle:
+        json.dump(value, handle)
+
+
+def delete(key):
+    try:
+        os.remove(_path(key))
+    except OSError:
+        pass
+
+
+def acquire_lock(name):
+    """Serialize invocations that share a resource (overlay, filenames).
+
+    Two area captures triggered at once would otherwise fight over the
+    selection overlay and can clobber each other's timestamped filename;
+    the second invocation queues until the first finishes. Returns an open
+    handle holding the lock; closing it (or process exit) releases.
+    """
+    import fcntl
+    import sys
+
+    os.makedirs(STATE_DIR, exist_ok=True)
+    handle = open(os.path.join(STATE_DIR, name + ".lock"), "w")
+    try:
+        fcntl.flock(handle, fcntl.LOCK_EX | fcntl.LOCK_NB)
+    except OSError:
+        print("waiting for another %s instance to finish" % name, file=sys.stderr)
+        fcntl.flock(handle, fcntl.LOCK_EX)
+    return handle
+
+
+def reset():
+    """Wipe all runtime state (for `openshotx state reset`)."""
+    shutil.rmtree(STATE_DIR, ignore_errors=True)
@@ -0,0 +1,189 @@
+import os
+import shutil
+import tempfile
+import time
+
+from capture.screenshot import CaptureError
+from utils import sandbox, state
+
+
+class SaveError(CaptureError):
+    pass
+
+
+DEFAULT_SAVE_DIR = os.path.expanduser("~/Pictures/OpenShotX")
+TEMP_DIR = os.path.join(
+    os.environ.get("XDG_CACHE_HOME", os.path.expanduser("~/.cache")), "openshotx", "temp"
+)
+TEMP_MAX_AGE = 24 * 60 * 60  # expire ephemeral captures after a day
+
+
+def default_save_dir():
+    """Where captures go by default, sandbox-aware.
+
+    In a Flatpak/Snap the literal ~/Pictures may be a hidden per-app dir, so
+    resolve the real Pictures folder through xdg-user-dir instead.
+    """
+    if sandbox.sandbox_kind():
+        return os.path.join(sandbox.pictures_dir(), "OpenShotX")
+    return DEFAULT_SAVE_DIR
+
+
+def default_filename(extension="png", scale=1.0):
+    """Timestamped default name; HiDPI captures get an @2x-style suffix."""
+    suffix = "@%gx" % scale if scale and scale > 1 else ""
+    return time.strftime("Screenshot %Y-%m-%d at %H.%M.%S") + suffix + "." + extension
+
+
+def preflight_check(path=None, expected_bytes=16 * 1024 * 1024):
+    """Fail fast if the output location is unwritable or the disk is nearly full.
+
+    Run before the (possibly interactive) capture so the user is not asked to
+    drag out a selection only to lose it to a permissions error afterwards.
+    """
+    directory = os.path.dirname(path) if path else default_save_dir()
+    directory = directory or "."
+    probe = directory
+    while probe and not os.path.isdir(probe):
+        probe = os.path.dirname(probe)
+    if not probe:
+        probe = "."
+    if not os.access(probe, os.W_OK):
+        raise SaveError("output directory %s is not writable" % directory)
+    if shutil.disk_usage(probe).free < expected_bytes:
+        raise SaveError("not enough disk space in %s" % directory)
+
+
+def save_capture(
+    capture,
+    path=None,
+    extension="png",
+    subdir_template=None,
+    quality=None,
+    fsync=False,
+    progress=None,
+):
+    """Write a capture to disk, defaulting to the OpenShotX pictures folder.
+
+    subdir_template is a strftime pattern (e.g. '%Y/%m') that sorts captures
+    into dated subfolders under the save dir; configured as [save] subdirs.
+    progress, when given, is called with (fraction, message) around the
+    encode/write so callers can show the save isn't hung on a slow mount.
+    """
+    if path is None:
+        directory = default_save_dir()
+        if subdir_template:
+            directory = os.path.join(directory, time.strftime(subdir_template))
+        os.makedirs(directory, exist_ok=True)
+        path = os.path.join(
+            directory, default_filename(extension, scale=getattr(capture, "scale", 1.0))
+        )
+    image = capture.image
+    resolution = None
+    if path.lower().endswith((".jpg", ".jpeg", ".pdf")):
+        image = image.convert("RGB")  # neither JPEG nor PDF has an alpha channel
+    if path.lower().endswith(".pdf"):
+        # The page size comes from pixel count / resolution, so a 2x
+        # capture lands on paper at the same physical size as a 1x one.
+        resolution = 96 * (getattr(capture, "scale", 1.0) or 1.0)
+    if progress:
+        progress(0.0, "encoding " + os.path.basename(path))
+    _write_atomic(image, path, quality=quality, fsync=fsync, resolution=resolution)
+    if progress:
+        progress(1.0, "saved " + os.path.basename(path))
+    return path
+
+
+def save_to_vault(capture, config, extension="png"):
+    """Save into an Obsidian/Logseq vault's attachments folder.
+
+    Uses the vault's own naming convention (Obsidian's "Pasted image
+    <timestamp>" by default, overridable as [vault] name) so the files sit
+    indistinguishably next to ones pasted in the app. Returns (path,
+    basename); the caller copies the wiki-link embed.
+
+        [vault]
+        path = ~/Notes
+        attachments = attachments
+    """
+    vault = config.get("vault", "path")
+    if not vault:
+        raise SaveError("set path under [vault] in the config to use the vault sink")
+    directory = os.path.join(
+        os.path.expanduser(vault), config.get("vault", "attachments", fallback="attachments")
+    )
+    os.makedirs(directory, exist_ok=True)
+    template = config.get("vault", "name", fallback="Pasted image %Y%m%d%H%M%S")
+    name = time.strftime(template) + "." + extension
+    path = os.path.join(directory, name)
+    image = capture.image
+    if extension in ("jpg", "jpeg"):
+        image = image.convert("RGB")
+    _write_atomic(image, path)
+    return path, name
+
+
+def _write_atomic(image, path, quality=None, fsync=False, resolution=None):
+    """Write via a temp file in the target directory and rename into place.
+
+    An interrupted save (disk full, Ctrl-C) leaves no truncated image behind
+    to break history or upload features later.
+    """
+    directory = os.path.dirname(path) or "."
+    suffix = os.path.splitext(path)[1]
+    options = {}
+    if quality is not None:
+        options["quality"] = quality
+    if resolution is not None:
+        options["resolution"] = resolution
+    fd, temp_path = tempfile.mkstemp(dir=directory, prefix=".openshotx-", suffix=suffix)
+    try:
+        with os.fdopen(fd, "wb") as handle:
+            image.save(handle, format=_pil_format(suffix), **options)
+            if fsync:
+                handle.flush()
+                os.fsync(handle.fileno())
+        os.replace(temp_path, path)
+    except BaseException:
+        try:
+            os.remove(temp_path)
+        except OSError:
+            pass
+        raise
+
+
+def _pil_format(suffix):
+    suffix = suffix.lower().lstrip(".")
+    return {"jpg": "JPEG", "jpeg": "JPEG"}.get(suffix, suffix.upper() or "PNG")
+
+
+def save_temp_capture(capture, extension="png"):
+    """Save into the cache for one-off uploads/pastes; expires automatically."""
+    os.makedirs(TEMP_DIR, exist_ok=True)
+    path = os.path.join(TEMP_DIR, default_filename(extension))
+    _write_atomic(capture.image, path)
+    return path
+
+
+def clean_expired_temp():
+    """Drop ephemeral captures older than TEMP_MAX_AGE. Called on every run."""
+    if not os.path.isdir(TEMP_DIR):
+        return
+    cutoff = time.time() - TEMP_MAX_AGE
+    for name in os.listdir(TEMP_DIR):
+        path = os.path.join(TEMP_DIR, name)
+        try:
+            if os.path.getmtime(path) < cutoff:
+                os.remove(path)
+        except OSError:
+            pass  # raced with another instance; nothing to do
+
+
+def record_last_capture(params):
+    """Remember the parameters of a capture so `openshotx redo` can replay it."""
+    state.set("last_capture", params)
+
+
+def load_last_capture():
+    """Parameters of the previous capture, or None if there is no history yet."""
+    return state.get("last_capture")
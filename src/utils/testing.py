@@ -0,0 +1,134 @@
+import os
+import subprocess
+import time
+
+from capture.screenshot import CaptureError
+
+
+class GoldenMismatch(AssertionError):
+    """Raised when a capture drifts beyond tolerance from its reference image."""
+
+    def __init__(self, difference, tolerance, diff_path):
+        self.difference = difference
+        self.diff_path = diff_path
+        message = "image differs from golden by %.4f (tolerance %.4f)" % (
+            difference, tolerance,
+        )
+        if diff_path:
+            message += "; diff written to %s" % diff_path
+        super().__init__(message)
+
+
+def image_difference(image, reference):
+    """Mean per-pixel difference between two images, normalized to 0..1."""
+    from PIL import ImageChops
+
+    image = image.convert("RGB")
+    reference = reference.convert("RGB")
+    if image.size != reference.size:
+        return 1.0
+    diff = ImageChops.difference(image, reference)
+    histogram = diff.histogram()
+    total = 0
+    count = image.width * image.height * 3
+    for channel in range(3):
+        for value, bucket in enumerate(histogram[channel * 256:(channel + 1) * 256]):
+            total += value * bucket
+    return total / (count * 255.0)
+
+
+def assert_matches_golden(source, reference_path, tolerance=0.01, diff_path=None):
+    """Compare a capture (or PIL image) against a stored reference PNG.
+
+    On first run the reference is created from the capture. On mismatch a
+    per-pixel diff artifact is written (next to the reference by default) and
+    GoldenMismatch is raised, making this directly usable from GUI
+    visual-regression tests.
+    """
+    from PIL import Image, ImageChops
+
+    image = getattr(source, "image", source)
+    if not os.path.exists(reference_path):
+        image.convert("RGB").save(reference_path)
+        return
+    reference = Image.open(reference_path)
+    difference = image_difference(image, reference)
+    if difference <= tolerance:
+        return
+    if diff_path is None:
+        base, ext = os.path.splitext(reference_path)
+        diff_path = base + ".diff" + (ext or ".png")
+    if image.size == reference.size:
+        ImageChops.difference(image.convert("RGB"), reference.convert("RGB")).save(diff_path)
+    else:
+        diff_path = None  # size mismatch: no meaningful pixel diff to write
+    raise GoldenMismatch(difference, tolerance, diff_path)
+
+
+def capture_matches_golden(region, reference_path, tolerance=0.01, display=None):
+    """Capture a screen region and assert it matches the stored golden image."""
+    from capture.screenshot import capture_region
+
+    assert_matches_golden(capture_region(region, display=display), reference_path, tolerance)
+
+
+class VirtualDisplay:
+    """Spawn a headless Xvfb server for CI screenshot tests.
+
+    Use as a context manager; the display string works with the --display
+    capture option and with the functions in capture.screenshot:
+
+        with VirtualDisplay(width=1280, height=720) as display:
+            data = capture_fullscreen(display=display.name)
+    """
+
+    def __init__(self, number=None, width=1280, height=720, depth=24):
+        self.number = number
+        self.width = width
+        self.height = height
+        self.depth = depth
+        self.process = None
+        self.name = None
+
+    def start(self):
+        number = self.number if self.number is not None else self._free_number()
+        self.name = ":%d" % number
+        try:
+            self.process = subprocess.Popen(
+                [
+                    "Xvfb", self.name,
+                    "-screen", "0",
+                    "%dx%dx%d" % (self.width, self.height, self.depth),
+                ],
+                stdout=subprocess.DEVNULL,
+                stderr=subprocess.DEVNULL,
+            )
+        except OSError:
+            raise CaptureError("Xvfb is not installed")
+        # Give the server a moment to come up before clients connect.
+        for _ in range(50):
+            if os.path.exists("/tmp/.X11-unix/X%d" % number):
+                return self
+            if self.process.poll() is not None:
+                raise CaptureError("Xvfb exited early (display %s taken?)" % self.name)
+            time.sleep(0.1)
+        raise CaptureError("Xvfb did not come up on %s" % self.name)
+
+    def stop(self):
+        if self.process is not None:
+            self.process.terminate()
+            self.process.wait()
+            self.process = None
+
+    @staticmethod
+    def _free_number():
+        number = 99
+        while os.path.exists("/tmp/.X11-unix/X%d" % number):
+            number += 1
+        return number
+
+    def __enter__(self):
+        return self.start()
+
+    def __exit__(self, exc_type, exc_value, traceback):
+        self.stop()
@@ -0,0 +1,86 @@
+"""Unit tests for the raw X pixel-word decoders.
+
+The decode paths in capture.screenshot are pure byte-shuffling, so they
+are exercised here against tiny hand-built buffers — no display, grabber,
+or Qt needed: LSB vs MSB word layout, row padding via stride, and the
+depth-30 x2r10g10b10 unpack.
+
+    python3 -m unittest discover -s tests
+"""
+import os
+import struct
+import sys
+import unittest
+
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), "..", "src"))
+
+from capture.screenshot import raw30_to_capture, raw_to_capture  # noqa: E402
+
+
+def _word_lsb(r, g, b, a=255):
+    """One 32-bit ARGB pixel as an LSB-first X server delivers it."""
+    return bytes((b, g, r, a))
+
+
+def _word_msb(r, g, b, a=255):
+    """The same pixel from an MSB-first server: A,R,G,B."""
+    return bytes((a, r, g, b))
+
+
+class RawDecodeTest(unittest.TestCase):
+    def test_lsb_words_decode_to_rgba(self):
+        buffer = _word_lsb(255, 0, 0) + _word_lsb(10, 20, 30, 40)
+        data = raw_to_capture(buffer, 2, 1)
+        self.assertEqual(data.image.getpixel((0, 0)), (255, 0, 0, 255))
+        self.assertEqual(data.image.getpixel((1, 0)), (10, 20, 30, 40))
+
+    def test_msb_words_match_lsb_decode(self):
+        pixels = [(255, 0, 0, 255), (1, 2, 3, 4), (200, 150, 100, 50)]
+        lsb = b"".join(_word_lsb(*p) for p in pixels)
+        msb = b"".join(_word_msb(*p) for p in pixels)
+        expected = raw_to_capture(lsb, 3, 1)
+        decoded = raw_to_capture(msb, 3, 1, byte_order="MSB")
+        self.assertEqual(list(decoded.image.getdata()), list(expected.image.getdata()))
+
+    def test_stride_padding_is_skipped(self):
+        # Two 2px rows padded to a 12-byte scanline unit; junk in the pad
+        # bytes must not shear the second row.
+        row0 = _word_lsb(1, 2, 3) + _word_lsb(4, 5, 6) + b"\xde\xad\xbe\xef"
+        row1 = _word_lsb(7, 8, 9) + _word_lsb(10, 11, 12) + b"\xde\xad\xbe\xef"
+        data = raw_to_capture(row0 + row1, 2, 2, stride=12)
+        self.assertEqual(data.stride, 12)
+        self.assertEqual(data.image.getpixel((0, 1)), (7, 8, 9, 255))
+        self.assertEqual(data.image.getpixel((1, 1)), (10, 11, 12, 255))
+
+
+class Raw30DecodeTest(unittest.TestCase):
+    @staticmethod
+    def _word(r10, g10, b10):
+        return (r10 << 20) | (g10 << 10) | b10
+
+    def test_channels_reduce_to_top_eight_bits(self):
+        buffer = struct.pack(
+            "<2I", self._word(0x3FF, 0, 0), self._word(0, 0x200, 0x155)
+        )
+        data = raw30_to_capture(buffer, 2, 1)
+        self.assertEqual(data.image.getpixel((0, 0)), (255, 0, 0, 255))
+        self.assertEqual(data.image.getpixel((1, 0)), (0, 0x80, 0x55, 255))
+
+    def test_msb_words_match_lsb_decode(self):
+        words = [self._word(0x123, 0x256, 0x389), self._word(0x3FF, 0x3FF, 0x3FF)]
+        expected = raw30_to_capture(struct.pack("<2I", *words), 2, 1)
+        decoded = raw30_to_capture(struct.pack(">2I", *words), 2, 1, byte_order="MSB")
+        self.assertEqual(list(decoded.image.getdata()), list(expected.image.getdata()))
+
+    def test_stride_padding_is_skipped(self):
+        rows = [
+            struct.pack("<I4s", self._word(0x3FF, 0, 0), b"junk"),
+            struct.pack("<I4s", self._word(0, 0x3FF, 0), b"junk"),
+        ]
+        data = raw30_to_capture(b"".join(rows), 1, 2, stride=8)
+        self.assertEqual(data.image.getpixel((0, 0)), (255, 0, 0, 255))
+        self.assertEqual(data.image.getpixel((0, 1)), (0, 255, 0, 255))
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,53 @@
+"""End-to-end check of the visual regression harness itself.
+
+Drives a headless Xvfb server through utils.testing.VirtualDisplay and
+round-trips a capture through the golden comparison, so the harness GUI
+tests rely on is itself known to work. Skips itself when Xvfb or a
+grabber isn't installed, so it is safe to run anywhere:
+
+    python3 -m unittest discover -s tests
+"""
+import os
+import shutil
+import sys
+import tempfile
+import unittest
+
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), "..", "src"))
+
+from capture.screenshot import CaptureError, capture_fullscreen  # noqa: E402
+from utils.testing import (  # noqa: E402
+    GoldenMismatch,
+    VirtualDisplay,
+    assert_matches_golden,
+)
+
+
+@unittest.skipUnless(shutil.which("Xvfb"), "Xvfb not installed")
+class GoldenHarnessTest(unittest.TestCase):
+    def test_capture_round_trips_through_golden(self):
+        with VirtualDisplay(width=640, height=480) as display:
+            try:
+                data = capture_fullscreen(display=display.name)
+            except CaptureError as exc:
+                self.skipTest("no grabber available: %s" % exc)
+            self.assertEqual((data.width, data.height), (640, 480))
+            with tempfile.TemporaryDirectory() as directory:
+                golden = os.path.join(directory, "fullscreen.png")
+                # The first run seeds the reference...
+                assert_matches_golden(data, golden)
+                self.assertTrue(os.path.exists(golden))
+                # ...an identical capture matches it...
+                assert_matches_golden(data, golden)
+                # ...and a clearly different frame trips the comparison
+                # and leaves a diff artifact behind.
+                inverted = data.image.point(lambda value: 255 - value)
+                with self.assertRaises(GoldenMismatch):
+                    assert_matches_golden(inverted, golden, tolerance=0.01)
+                self.assertTrue(
+                    os.path.exists(os.path.join(directory, "fullscreen.diff.png"))
+                )
+
+
+if __name__ == "__main__":
+    unittest.main()